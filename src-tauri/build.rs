@@ -1,3 +1,11 @@
 fn main() {
+    // The CLI has no Tauri context; hand it the app identifier so it finds
+    // the same app data dir.
+    println!("cargo:rerun-if-changed=tauri.conf.json");
+    let conf = std::fs::read_to_string("tauri.conf.json").expect("read tauri.conf.json");
+    let conf: serde_json::Value = serde_json::from_str(&conf).expect("parse tauri.conf.json");
+    let identifier = conf["identifier"].as_str().expect("tauri.conf.json has no identifier");
+    println!("cargo:rustc-env=RADCONTROL_APP_IDENTIFIER={identifier}");
+
     tauri_build::build()
 }
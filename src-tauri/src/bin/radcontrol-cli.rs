@@ -0,0 +1,408 @@
+// Headless front end to the same registry, verb allowlist and history the
+// desktop app uses, for dev boxes reached over SSH where no window runs.
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use radcontrol_app_lib::commands::history::{HistoryFilter, HistoryStore};
+use radcontrol_app_lib::commands::jobs::JobTable;
+use radcontrol_app_lib::commands::logs::LogStore;
+use radcontrol_app_lib::commands::o2::{check_verb, run_o2_recorded, O2Call, RunO2Result};
+use radcontrol_app_lib::commands::ports::port_status;
+use radcontrol_app_lib::commands::registry::{load_registry, Project};
+use radcontrol_app_lib::snapshot::write_snapshot;
+
+const USAGE: &str = "\
+usage: radcontrol-cli [--json] <command>
+
+commands:
+  projects list                      registry rows
+  ports                              listening state of every project port
+  run <verb> [--arg name=value]...   run an allowlisted O2 verb
+      --yes --confirm <phrase>       required for destructive verbs; the phrase
+                                     is the verb's confirm phrase, or its name
+  snapshot <project>                 write docs/_repo_snapshot.txt
+  history [--verb V] [--project P] [--limit N]
+";
+
+// `identifier` from tauri.conf.json (see build.rs), so history is shared with the app.
+const APP_IDENTIFIER: &str = env!("RADCONTROL_APP_IDENTIFIER");
+
+#[derive(Debug)]
+enum CliError {
+    Usage(String),
+    Failed(String),
+}
+
+impl From<String> for CliError {
+    fn from(e: String) -> Self {
+        CliError::Failed(e)
+    }
+}
+
+type CliResult = Result<ExitCode, CliError>;
+
+/// Where Tauri's app_data_dir() points on Linux.
+fn data_dir() -> Result<PathBuf, String> {
+    let base = match std::env::var_os("XDG_DATA_HOME").filter(|v| !v.is_empty()) {
+        Some(d) => PathBuf::from(d),
+        None => {
+            let home = std::env::var_os("HOME").ok_or("HOME is not set")?;
+            PathBuf::from(home).join(".local/share")
+        }
+    };
+    Ok(base.join(APP_IDENTIFIER))
+}
+
+fn print_json<T: Serialize>(v: &T) -> Result<(), String> {
+    let s = serde_json::to_string_pretty(v).map_err(|e| format!("Failed to serialize output: {e}"))?;
+    println!("{s}");
+    Ok(())
+}
+
+fn fmt_time(ms: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(ms as i64)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Serialized name of a snake_case enum value, for table output.
+fn label<T: Serialize>(v: &T) -> String {
+    match serde_json::to_value(v) {
+        Ok(Value::String(s)) => s,
+        _ => "?".to_string(),
+    }
+}
+
+/// Pull `--flag value` out of `args`.
+fn take_opt(args: &mut Vec<String>, flag: &str) -> Result<Option<String>, CliError> {
+    let Some(i) = args.iter().position(|a| a == flag) else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        return Err(CliError::Usage(format!("{flag} needs a value")));
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    Ok(Some(value))
+}
+
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let before = args.len();
+    args.retain(|a| a != flag);
+    args.len() != before
+}
+
+fn no_more(args: &[String]) -> Result<(), CliError> {
+    match args.first() {
+        Some(extra) => Err(CliError::Usage(format!("unexpected argument: {extra}"))),
+        None => Ok(()),
+    }
+}
+
+fn find_project(key: &str) -> Result<Project, String> {
+    load_registry()?
+        .projects
+        .into_iter()
+        .find(|p| p.key == key)
+        .ok_or_else(|| format!("unknown project: {key}"))
+}
+
+fn cmd_projects(mut args: Vec<String>, as_json: bool) -> CliResult {
+    match args.first().map(String::as_str) {
+        Some("list") => {
+            args.remove(0);
+        }
+        _ => return Err(CliError::Usage("expected: projects list".to_string())),
+    }
+    no_more(&args)?;
+
+    let reg = load_registry()?;
+    if as_json {
+        print_json(&reg)?;
+    } else {
+        for p in &reg.projects {
+            let port = p.port.map(|n| format!(":{n}")).unwrap_or_else(|| "-".to_string());
+            let repo = p.repo_path.as_deref().or(p.repo_hint.as_deref()).unwrap_or("-");
+            println!("{:<24} {:<28} {:<7} {}", p.key, p.label, port, repo);
+        }
+        for e in &reg.errors {
+            eprintln!("skipped row {}: {}", e.index, e.message);
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn cmd_ports(args: Vec<String>, as_json: bool) -> CliResult {
+    no_more(&args)?;
+
+    let projects: Vec<Project> = load_registry()?.projects.into_iter().filter(|p| p.port.is_some()).collect();
+    let ports: Vec<u16> = projects.iter().filter_map(|p| p.port).collect();
+    let statuses = port_status(&ports);
+
+    if as_json {
+        let rows: Vec<Value> = projects
+            .iter()
+            .zip(&statuses)
+            .map(|(p, s)| json!({ "key": p.key, "status": s }))
+            .collect();
+        print_json(&rows)?;
+    } else {
+        for (p, s) in projects.iter().zip(&statuses) {
+            let state = if s.listening { "LISTENING" } else { "stopped" };
+            let owner = match (s.pid, &s.cmd) {
+                (Some(pid), Some(cmd)) => format!("{pid} {cmd}"),
+                (Some(pid), None) => pid.to_string(),
+                _ => s.err.clone().unwrap_or_default(),
+            };
+            println!("{:<24} :{:<6} {:<10} {}", p.key, s.port, state, owner);
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn exit_for(run: &RunO2Result) -> ExitCode {
+    if run.ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(u8::try_from(run.code).ok().filter(|c| *c != 0).unwrap_or(1))
+    }
+}
+
+/// Gate for destructive verbs: both an explicit --yes and the phrase typed
+/// out, so neither a stray flag nor a pasted command line runs it alone. The
+/// phrase is the verb's `confirm_phrase`, or the verb itself.
+fn confirm_destructive(verb: &str, confirm_phrase: Option<&str>, yes: bool, phrase: Option<&str>) -> Result<(), CliError> {
+    let expected = confirm_phrase.unwrap_or(verb).trim();
+    if !yes || phrase.map(str::trim) != Some(expected) {
+        return Err(CliError::Failed(format!(
+            "{verb} is destructive; pass --yes --confirm {expected:?} to run it"
+        )));
+    }
+    Ok(())
+}
+
+/// Run a verb the same way the app does, recorded with trigger "cli". Output
+/// streams to the terminal unless `--json` asks for the result object.
+fn run_verb(verb: &str, raw_args: Map<String, Value>, yes: bool, phrase: Option<&str>, as_json: bool) -> CliResult {
+    let (info, args) = check_verb(verb, Some(&raw_args)).map_err(|e| e.message)?;
+    if info.is_destructive() {
+        confirm_destructive(&info.verb, info.meta.confirm_phrase.as_deref(), yes, phrase)?;
+    }
+
+    let data_dir = data_dir()?;
+    let call = O2Call::new(info.verb, args, "cli");
+    let (history, logs) = (HistoryStore::new(&data_dir), LogStore::headless(&data_dir));
+    let run = run_o2_recorded(&JobTable::default(), &history, &logs, &call, |stream, line| {
+        if as_json {
+            return;
+        }
+        if stream == "stdout" {
+            println!("{line}");
+        } else {
+            eprintln!("{line}");
+        }
+    });
+
+    if as_json {
+        print_json(&json!({ "jobId": call.job_id, "verb": call.verb, "result": run }))?;
+    } else if !run.ok {
+        let why = if run.timed_out {
+            "timed out".to_string()
+        } else {
+            format!("exit code {}", run.code)
+        };
+        eprintln!("{} failed ({why}) in {}ms", call.verb, run.elapsed_ms);
+    }
+    Ok(exit_for(&run))
+}
+
+/// `run` arguments after parsing.
+#[derive(Debug)]
+struct RunArgs {
+    verb: String,
+    args: Map<String, Value>,
+    yes: bool,
+    phrase: Option<String>,
+}
+
+fn parse_run(mut args: Vec<String>) -> Result<RunArgs, CliError> {
+    let yes = take_flag(&mut args, "--yes");
+    let phrase = take_opt(&mut args, "--confirm")?;
+    let mut raw_args = Map::new();
+    while let Some(pair) = take_opt(&mut args, "--arg")? {
+        let (name, value) = pair
+            .split_once('=')
+            .ok_or_else(|| CliError::Usage(format!("--arg expects name=value, got {pair}")))?;
+        raw_args.insert(name.to_string(), Value::String(value.to_string()));
+    }
+    if args.is_empty() {
+        return Err(CliError::Usage("expected: run <verb>".to_string()));
+    }
+    let verb = args.remove(0);
+    no_more(&args)?;
+
+    Ok(RunArgs {
+        verb,
+        args: raw_args,
+        yes,
+        phrase,
+    })
+}
+
+fn cmd_run(args: Vec<String>, as_json: bool) -> CliResult {
+    let run = parse_run(args)?;
+    run_verb(&run.verb, run.args, run.yes, run.phrase.as_deref(), as_json)
+}
+
+/// Like the Snapshot button: the project's snapshot verb if it has one,
+/// otherwise the native snapshot.
+fn cmd_snapshot(mut args: Vec<String>, as_json: bool) -> CliResult {
+    if args.is_empty() {
+        return Err(CliError::Usage("expected: snapshot <project>".to_string()));
+    }
+    let key = args.remove(0);
+    no_more(&args)?;
+
+    let project = find_project(&key)?;
+    // An empty hook is an unset one.
+    if let Some(verb) = project.o2_snapshot_key.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+        return run_verb(verb, Map::new(), false, None, as_json);
+    }
+
+    let s = write_snapshot(&project.label, &project.repo_dir()?)?;
+    if as_json {
+        print_json(&s)?;
+    } else {
+        println!(
+            "wrote {} ({}, {} dirty)",
+            s.path,
+            s.branch.as_deref().unwrap_or("no git"),
+            s.dirty_files
+        );
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn cmd_history(mut args: Vec<String>, as_json: bool) -> CliResult {
+    let limit = take_opt(&mut args, "--limit")?
+        .map(|n| n.parse::<usize>().map_err(|_| CliError::Usage(format!("--limit: not a number: {n}"))))
+        .transpose()?;
+    let filter = HistoryFilter {
+        verb: take_opt(&mut args, "--verb")?,
+        project: take_opt(&mut args, "--project")?,
+        limit,
+        ..Default::default()
+    };
+    no_more(&args)?;
+
+    let rows = HistoryStore::new(&data_dir()?).list(&filter)?;
+    if as_json {
+        print_json(&rows)?;
+    } else {
+        for r in &rows {
+            println!(
+                "{}  {:<10} {:>4}  {:<9} {:<32} {}",
+                fmt_time(r.started_at_ms),
+                label(&r.status),
+                r.code,
+                r.trigger,
+                r.verb,
+                r.id
+            );
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let as_json = take_flag(&mut args, "--json");
+    if args.is_empty() || take_flag(&mut args, "--help") || take_flag(&mut args, "-h") {
+        print!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    let cmd = args.remove(0);
+    let res = match cmd.as_str() {
+        "projects" => cmd_projects(args, as_json),
+        "ports" => cmd_ports(args, as_json),
+        "run" => cmd_run(args, as_json),
+        "snapshot" => cmd_snapshot(args, as_json),
+        "history" => cmd_history(args, as_json),
+        other => Err(CliError::Usage(format!("unknown command: {other}"))),
+    };
+
+    let _ = std::io::stdout().flush();
+    match res {
+        Ok(code) => code,
+        Err(CliError::Usage(msg)) => {
+            eprintln!("radcontrol-cli: {msg}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+        Err(CliError::Failed(msg)) => {
+            if as_json {
+                println!("{}", json!({ "error": msg }));
+            } else {
+                eprintln!("radcontrol-cli: {msg}");
+            }
+            ExitCode::from(1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(s: &str) -> Vec<String> {
+        s.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn options_and_flags_are_taken_out() {
+        let mut args = argv("--verb a.b --limit 5 --json extra");
+        assert_eq!(take_opt(&mut args, "--limit").unwrap().as_deref(), Some("5"));
+        assert_eq!(take_opt(&mut args, "--project").unwrap(), None);
+        assert!(take_flag(&mut args, "--json"));
+        assert!(!take_flag(&mut args, "--json"));
+        assert_eq!(take_opt(&mut args, "--verb").unwrap().as_deref(), Some("a.b"));
+        assert_eq!(args, ["extra"]);
+        assert!(matches!(no_more(&args), Err(CliError::Usage(m)) if m == "unexpected argument: extra"));
+        assert!(no_more(&[]).is_ok());
+
+        let mut dangling = argv("--limit");
+        assert!(matches!(take_opt(&mut dangling, "--limit"), Err(CliError::Usage(m)) if m == "--limit needs a value"));
+    }
+
+    #[test]
+    fn run_arguments_parse_in_any_order() {
+        let run = parse_run(argv("--arg msg=a=b --yes db.reset --confirm wipe --arg n=1")).unwrap();
+        assert_eq!(run.verb, "db.reset");
+        assert!(run.yes);
+        assert_eq!(run.phrase.as_deref(), Some("wipe"));
+        assert_eq!(run.args.get("msg"), Some(&json!("a=b")));
+        assert_eq!(run.args.get("n"), Some(&json!("1")));
+
+        assert!(matches!(parse_run(argv("--yes")), Err(CliError::Usage(_))));
+        assert!(matches!(parse_run(argv("a b")), Err(CliError::Usage(_))));
+        assert!(matches!(parse_run(argv("a --arg novalue")), Err(CliError::Usage(m)) if m.contains("name=value")));
+    }
+
+    #[test]
+    fn destructive_verbs_need_yes_and_the_phrase() {
+        assert!(confirm_destructive("db.reset", None, true, Some("db.reset")).is_ok());
+        assert!(confirm_destructive("db.reset", None, true, Some(" db.reset ")).is_ok());
+        assert!(confirm_destructive("db.reset", Some("wipe prod"), true, Some("wipe prod")).is_ok());
+
+        for (yes, phrase) in [(false, Some("db.reset")), (true, None), (true, Some("db")), (false, None)] {
+            let err = confirm_destructive("db.reset", None, yes, phrase).unwrap_err();
+            assert!(matches!(err, CliError::Failed(m) if m.contains("--yes --confirm \"db.reset\"")));
+        }
+        // With a custom phrase the verb name alone isn't enough.
+        assert!(confirm_destructive("db.reset", Some("wipe prod"), true, Some("db.reset")).is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use super::history::HistoryStore;
+use super::jobs::JobTable;
+use super::logs::LogStore;
+use super::o2::{check_verb, run_o2_recorded, O2Args, O2Call, RunO2Result};
+use super::registry::{load_registry, Project};
+use crate::git::{bounded_diff, diff_summary, git, untracked_files, worktree_token, DiffSummary};
+
+/// Everything the commit verb would pick up, for review before committing.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitPreview {
+    pub key: String,
+    pub repo: String,
+    pub verb: String,
+    pub branch: Option<String>,
+    pub head: Option<String>,
+    pub staged: DiffSummary,
+    pub unstaged: DiffSummary,
+    pub untracked: Vec<String>,
+    /// Staged then unstaged unified diff, bounded.
+    pub diff: String,
+    pub diff_truncated: bool,
+    /// Pass back to o2_commit; it refuses if the tree changed in between.
+    pub token: String,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitResult {
+    pub key: String,
+    pub verb: String,
+    pub job_id: String,
+    pub run: RunO2Result,
+    pub previous_head: Option<String>,
+    pub new_head: Option<String>,
+    /// HEAD moved during the run.
+    pub committed: bool,
+}
+
+fn head(repo: &Path) -> Option<String> {
+    git(repo, &["rev-parse", "HEAD"]).map(|s| s.trim().to_string())
+}
+
+/// The project, its checkout and its commit verb; `args` are checked
+/// against the verb's params like any run_o2 call.
+fn commit_target(key: &str, args: &Map<String, Value>) -> Result<(Project, PathBuf, String, O2Args), String> {
+    let project = load_registry()?
+        .projects
+        .into_iter()
+        .find(|p| p.key == key)
+        .ok_or_else(|| format!("unknown project: {key}"))?;
+    let verb = project
+        .o2_commit_key
+        .clone()
+        .ok_or_else(|| format!("project {key} has no o2CommitKey"))?;
+    // The preview token is this flow's confirmation, so no o2_prepare_verb.
+    let (info, args) = check_verb(&verb, Some(args)).map_err(|e| e.message)?;
+    let verb = info.verb;
+
+    let repo = project.repo_dir()?;
+    if git(&repo, &["rev-parse", "--git-dir"]).is_none() {
+        return Err(format!("not a git repository: {}", repo.display()));
+    }
+    Ok((project, repo, verb, args))
+}
+
+fn preview(key: String, repo: &Path, verb: String) -> CommitPreview {
+    let (diff, diff_truncated) = bounded_diff(repo);
+
+    CommitPreview {
+        key,
+        repo: repo.display().to_string(),
+        verb,
+        branch: git(repo, &["symbolic-ref", "--short", "-q", "HEAD"]).map(|s| s.trim().to_string()),
+        head: head(repo),
+        staged: diff_summary(repo, true),
+        unstaged: diff_summary(repo, false),
+        untracked: untracked_files(repo),
+        diff,
+        diff_truncated,
+        token: worktree_token(repo),
+    }
+}
+
+/// Refuse to commit anything the preview behind `token` didn't show.
+fn check_unchanged(repo: &Path, token: &str) -> Result<(), String> {
+    if worktree_token(repo) != token {
+        return Err("working tree changed since the preview; review it again".to_string());
+    }
+    Ok(())
+}
+
+#[tauri::command(async)]
+pub fn o2_commit_preview(key: String) -> Result<CommitPreview, String> {
+    let (project, repo, verb, _) = commit_target(key.trim(), &Map::new())?;
+    Ok(preview(project.key, &repo, verb))
+}
+
+/// Run the project's commit verb with `message` passed as `O2_ARG_MESSAGE`.
+/// `token` must come from an o2_commit_preview of the same tree state.
+#[tauri::command(async)]
+pub fn o2_commit(
+    jobs: State<'_, JobTable>,
+    history: State<'_, HistoryStore>,
+    logs: State<'_, LogStore>,
+    key: String,
+    message: String,
+    token: String,
+) -> Result<CommitResult, String> {
+    let message = message.trim();
+    if message.is_empty() {
+        return Err("commit message is empty".to_string());
+    }
+
+    let args = Map::from_iter([("message".to_string(), json!(message))]);
+    let (project, repo, verb, args) = commit_target(key.trim(), &args)?;
+    check_unchanged(&repo, &token)?;
+
+    let previous_head = head(&repo);
+    let call = O2Call::new(verb, args, "ui");
+    let run = run_o2_recorded(&jobs, &history, &logs, &call, |_, _| {});
+    let new_head = head(&repo);
+
+    Ok(CommitResult {
+        key: project.key,
+        verb: call.verb,
+        job_id: call.job_id,
+        committed: new_head.is_some() && new_head != previous_head,
+        run,
+        previous_head,
+        new_head,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::MAX_PREVIEW_DIFF_BYTES;
+    use std::fs;
+    use std::process::Command;
+
+    fn init_repo(name: &str) -> PathBuf {
+        let repo = std::env::temp_dir().join(format!("radcontrol-commit-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&repo);
+        fs::create_dir_all(&repo).unwrap();
+        fs::write(repo.join("a.txt"), "one\n").unwrap();
+        run_git(&repo, &["init", "-q"]);
+        run_git(&repo, &["add", "-A"]);
+        run_git(&repo, &["commit", "-qm", "init"]);
+        repo
+    }
+
+    fn run_git(repo: &Path, args: &[&str]) {
+        let ok = Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status
+            .success();
+        assert!(ok, "git {args:?}");
+    }
+
+    #[test]
+    fn changes_after_the_preview_are_refused() {
+        let repo = init_repo("token");
+        fs::write(repo.join("a.txt"), "two\n").unwrap();
+        let p = preview("demo".to_string(), &repo, "demo.commit".to_string());
+        let unchanged = check_unchanged(&repo, &p.token);
+
+        fs::write(repo.join("a.txt"), "three\n").unwrap();
+        let edited = check_unchanged(&repo, &p.token);
+        fs::write(repo.join("a.txt"), "two\n").unwrap();
+        fs::write(repo.join("new.txt"), "x\n").unwrap();
+        let untracked = check_unchanged(&repo, &p.token);
+        fs::remove_file(repo.join("new.txt")).unwrap();
+        run_git(&repo, &["add", "a.txt"]);
+        let staged = check_unchanged(&repo, &p.token);
+        let _ = fs::remove_dir_all(&repo);
+
+        assert_eq!(unchanged, Ok(()));
+        assert!(edited.unwrap_err().contains("changed since the preview"));
+        assert!(untracked.is_err());
+        assert!(staged.is_err());
+        assert_eq!(p.unstaged.files.len(), 1);
+        assert!(p.diff.contains("+two"));
+        assert!(!p.diff_truncated);
+    }
+
+    #[test]
+    fn large_diffs_are_cut_at_the_cap() {
+        let repo = init_repo("cap");
+        let line = "é".repeat(40) + "\n";
+        let lines = MAX_PREVIEW_DIFF_BYTES / line.len() * 3;
+        fs::write(repo.join("big.txt"), line.repeat(lines)).unwrap();
+        run_git(&repo, &["add", "big.txt"]);
+        fs::write(repo.join("a.txt"), "two\n").unwrap();
+        let p = preview("demo".to_string(), &repo, "demo.commit".to_string());
+        let _ = fs::remove_dir_all(&repo);
+
+        assert!(p.diff_truncated);
+        assert!(p.diff.len() <= MAX_PREVIEW_DIFF_BYTES);
+        assert!(p.diff.len() > MAX_PREVIEW_DIFF_BYTES - 4);
+        assert!(!p.diff.contains('\u{FFFD}'));
+        // The counts still cover everything.
+        assert_eq!(p.staged.insertions as usize, lines);
+        assert_eq!(p.unstaged.files[0].path, "a.txt");
+    }
+}
@@ -0,0 +1,177 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+use super::jobs::now_ms;
+use super::o2::{check_verb, O2Args, O2Error, O2ErrorKind};
+use super::verbs::VerbInfo;
+
+// How long a prepared destructive verb stays runnable.
+const CONFIRM_TTL_MS: u64 = 2 * 60 * 1000;
+
+/// Returned by o2_prepare_verb; pass `token` (and the confirm phrase, when
+/// there is one) back to run_o2 / run_o2_stream.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PreparedVerb {
+    pub verb: String,
+    pub args: O2Args,
+    pub token: String,
+    pub expires_at_ms: u64,
+    pub destructive: bool,
+    pub description: Option<String>,
+    pub confirm_phrase: Option<String>,
+}
+
+struct Pending {
+    verb: String,
+    args: O2Args,
+    expires_at_ms: u64,
+}
+
+/// Outstanding confirmation tokens. Managed as Tauri state; each token is
+/// bound to one verb + args and is consumed by the run it authorizes.
+#[derive(Clone, Default)]
+pub struct ConfirmTokens {
+    pending: Arc<Mutex<HashMap<String, Pending>>>,
+}
+
+/// 128 bits from the OS CSPRNG, hex-encoded. Tokens authorize destructive
+/// runs, so they must not be guessable.
+fn new_token() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(&mut bytes))
+        .map_err(|e| format!("Failed to read /dev/urandom: {e}"))?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+impl ConfirmTokens {
+    pub fn issue(&self, verb: &str, args: &O2Args) -> Result<(String, u64), String> {
+        let token = new_token()?;
+        let now = now_ms();
+        let expires_at_ms = now + CONFIRM_TTL_MS;
+
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.retain(|_, p| p.expires_at_ms > now);
+        pending.insert(
+            token.clone(),
+            Pending {
+                verb: verb.to_string(),
+                args: args.clone(),
+                expires_at_ms,
+            },
+        );
+        Ok((token, expires_at_ms))
+    }
+
+    /// Consume `token` if it was issued for exactly this verb and args.
+    fn redeem(&self, token: &str, verb: &str, args: &O2Args) -> Result<(), String> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let p = pending
+            .remove(token)
+            .ok_or_else(|| "unknown or already used confirmation token".to_string())?;
+        if p.expires_at_ms <= now_ms() {
+            return Err("confirmation token expired; prepare the verb again".to_string());
+        }
+        if p.verb != verb || &p.args != args {
+            return Err("confirmation token was issued for a different verb or arguments".to_string());
+        }
+        Ok(())
+    }
+
+    /// Gate for destructive verbs: a token from o2_prepare_verb plus the
+    /// verb's confirm phrase, if it declares one. Other verbs pass through.
+    pub fn check(
+        &self,
+        info: &VerbInfo,
+        args: &O2Args,
+        token: Option<&str>,
+        phrase: Option<&str>,
+    ) -> Result<(), O2Error> {
+        if !info.is_destructive() {
+            return Ok(());
+        }
+        let err = |kind, message: String| O2Error {
+            kind,
+            message,
+            verb: info.verb.clone(),
+        };
+
+        let Some(token) = token.map(str::trim).filter(|t| !t.is_empty()) else {
+            return Err(err(
+                O2ErrorKind::ConfirmationRequired,
+                format!("{} is destructive; call o2_prepare_verb and confirm first", info.verb),
+            ));
+        };
+        if let Some(expected) = &info.meta.confirm_phrase {
+            if phrase.map(str::trim) != Some(expected.trim()) {
+                return Err(err(
+                    O2ErrorKind::ConfirmationInvalid,
+                    format!("confirmation phrase does not match for {}", info.verb),
+                ));
+            }
+        }
+        self.redeem(token, &info.verb, args)
+            .map_err(|e| err(O2ErrorKind::ConfirmationInvalid, e))
+    }
+}
+
+/// Validate a verb + args and, for destructive verbs, issue a one-shot
+/// confirmation token. Non-destructive verbs get a token too; run_o2 just
+/// doesn't require it.
+pub fn prepare_verb(
+    tokens: &ConfirmTokens,
+    verb: &str,
+    args: Option<&Map<String, Value>>,
+) -> Result<PreparedVerb, O2Error> {
+    let (info, args) = check_verb(verb, args)?;
+    let (token, expires_at_ms) = tokens.issue(&info.verb, &args).map_err(|message| O2Error {
+        kind: O2ErrorKind::ConfirmationRequired,
+        message,
+        verb: info.verb.clone(),
+    })?;
+
+    Ok(PreparedVerb {
+        destructive: info.is_destructive(),
+        description: info.meta.description,
+        confirm_phrase: info.meta.confirm_phrase,
+        verb: info.verb,
+        args,
+        token,
+        expires_at_ms,
+    })
+}
+
+#[tauri::command]
+pub fn o2_prepare_verb(
+    tokens: State<'_, ConfirmTokens>,
+    verb: String,
+    args: Option<Map<String, Value>>,
+) -> Result<PreparedVerb, O2Error> {
+    prepare_verb(&tokens, &verb, args.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_random_and_single_use() {
+        let a = new_token().unwrap();
+        assert_eq!(a.len(), 32);
+        assert!(a.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(a, new_token().unwrap());
+
+        let tokens = ConfirmTokens::default();
+        let args = O2Args::new();
+        let (token, _) = tokens.issue("tbis.reset", &args).unwrap();
+        assert!(tokens.redeem(&token, "tbis.other", &args).is_err());
+        let (token, _) = tokens.issue("tbis.reset", &args).unwrap();
+        assert!(tokens.redeem(&token, "tbis.reset", &args).is_ok());
+        assert!(tokens.redeem(&token, "tbis.reset", &args).is_err());
+    }
+}
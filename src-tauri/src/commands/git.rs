@@ -0,0 +1,47 @@
+use serde::Serialize;
+use std::thread;
+
+use super::registry::{load_registry, Project};
+use crate::git::{repo_status, RepoGitStatus};
+
+/// Git state of one registry row's checkout. `err` is set (and `git` is
+/// None) when the row has no repo path or it isn't a git repository.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectGitStatus {
+    pub key: String,
+    pub repo: Option<String>,
+    pub git: Option<RepoGitStatus>,
+    pub dirty: bool,
+    pub err: Option<String>,
+}
+
+fn project_git_status(p: &Project) -> ProjectGitStatus {
+    let repo = p.repo_dir();
+    let res = repo.as_ref().map_err(Clone::clone).and_then(|r| repo_status(r));
+    ProjectGitStatus {
+        key: p.key.clone(),
+        repo: repo.ok().map(|r| r.display().to_string()),
+        dirty: res.as_ref().is_ok_and(RepoGitStatus::is_dirty),
+        err: res.as_ref().err().cloned(),
+        git: res.ok(),
+    }
+}
+
+/// Git status of every registered project, or only `keys` if given.
+/// Repos are queried concurrently.
+#[tauri::command(async)]
+pub fn o2_git_status(keys: Option<Vec<String>>) -> Result<Vec<ProjectGitStatus>, String> {
+    let mut projects = load_registry()?.projects;
+    if let Some(keys) = keys {
+        projects.retain(|p| keys.contains(&p.key));
+    }
+
+    Ok(thread::scope(|s| {
+        let handles: Vec<_> = projects
+            .iter()
+            .map(|p| s.spawn(move || project_git_status(p)))
+            .collect();
+        handles.into_iter().filter_map(|h| h.join().ok()).collect()
+    }))
+}
@@ -0,0 +1,331 @@
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::registry::{load_registry, Project};
+
+pub const HEALTH_TIMEOUT: Duration = Duration::from_millis(1500);
+
+// Enough to find an expected marker near the top of a page.
+const MAX_RESPONSE_BYTES: usize = 64 * 1024;
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthStatus {
+    pub key: String,
+    pub url: String,
+    /// 2xx/3xx and, when `healthExpect` is configured, the body matched.
+    /// Meaningless when `checked` is false.
+    pub ok: bool,
+    /// False for urls the check can't probe (https); `err` says why. Such a
+    /// project is never reported unhealthy.
+    pub checked: bool,
+    pub status: Option<u16>,
+    pub latency_ms: Option<u64>,
+    pub body_matched: Option<bool>,
+    pub err: Option<String>,
+}
+
+struct HttpResponse {
+    status: u16,
+    body: String,
+}
+
+/// Split `http://host[:port][/path]` into its parts. There is no TLS client
+/// here; https urls are left unchecked (see `check_project`) rather than
+/// treated as plain http.
+fn split_url(url: &str) -> Result<(String, u16, String), String> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("health checks support http:// urls only (got {url})"))?;
+
+    let (authority, path) = match rest.find(['/', '?']) {
+        Some(i) => (&rest[..i], rest[i..].to_string()),
+        None => (rest, "/".to_string()),
+    };
+    let path = if path.starts_with('?') { format!("/{path}") } else { path };
+    let path = path.split('#').next().unwrap_or("/").to_string();
+
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((h, p)) if !h.is_empty() && !p.contains(']') => {
+            let port = p.parse::<u16>().map_err(|_| format!("invalid port in {url}"))?;
+            (h.to_string(), port)
+        }
+        _ => (authority.to_string(), 80),
+    };
+    if host.is_empty() {
+        return Err(format!("no host in {url}"));
+    }
+
+    Ok((host, port, path))
+}
+
+/// Minimal HTTP/1.1 GET with a hard overall deadline.
+fn http_get(url: &str, timeout: Duration) -> Result<HttpResponse, String> {
+    let deadline = Instant::now() + timeout;
+    let (host, port, path) = split_url(url)?;
+
+    let addrs: Vec<_> = (host.trim_matches(['[', ']']), port)
+        .to_socket_addrs()
+        .map_err(|e| format!("cannot resolve {host}: {e}"))?
+        .collect();
+
+    let remaining = || deadline.saturating_duration_since(Instant::now()).max(Duration::from_millis(1));
+
+    // `localhost` may resolve to both ::1 and 127.0.0.1; dev servers often
+    // bind only one of them.
+    let mut last_err = format!("cannot resolve {host}");
+    let mut stream = None;
+    for addr in &addrs {
+        match TcpStream::connect_timeout(addr, remaining()) {
+            Ok(s) => {
+                stream = Some(s);
+                break;
+            }
+            Err(e) => last_err = format!("connect failed: {e}"),
+        }
+    }
+    let mut stream = stream.ok_or(last_err)?;
+    stream.set_write_timeout(Some(remaining())).map_err(|e| e.to_string())?;
+
+    let req = format!(
+        "GET {path} HTTP/1.1\r\nHost: {host}:{port}\r\nUser-Agent: radcontrol-health\r\nAccept: */*\r\nConnection: close\r\n\r\n"
+    );
+    stream.write_all(req.as_bytes()).map_err(|e| format!("request failed: {e}"))?;
+
+    let mut raw = Vec::new();
+    let mut buf = [0u8; 8192];
+    while raw.len() < MAX_RESPONSE_BYTES {
+        if Instant::now() >= deadline {
+            break;
+        }
+        stream.set_read_timeout(Some(remaining())).map_err(|e| e.to_string())?;
+        match stream.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => raw.extend_from_slice(&buf[..n]),
+            Err(e) if raw.is_empty() => return Err(format!("no response: {e}")),
+            Err(_) => break,
+        }
+    }
+
+    parse_response(&raw)
+}
+
+fn parse_response(raw: &[u8]) -> Result<HttpResponse, String> {
+    let split = raw.windows(4).position(|w| w == b"\r\n\r\n");
+    let (head, body) = match split {
+        Some(i) => (&raw[..i], &raw[i + 4..]),
+        None => (raw, &[][..]),
+    };
+    let head = String::from_utf8_lossy(head);
+    let status = head
+        .lines()
+        .next()
+        .and_then(|l| l.split_whitespace().nth(1))
+        .and_then(|c| c.parse::<u16>().ok())
+        .ok_or_else(|| "malformed HTTP response".to_string())?;
+
+    let chunked = head.lines().skip(1).any(|l| {
+        l.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding") && value.to_ascii_lowercase().contains("chunked")
+        })
+    });
+    let body = if chunked { dechunk(body) } else { body.to_vec() };
+
+    Ok(HttpResponse {
+        status,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+/// Decode a chunked body. The read may stop at MAX_RESPONSE_BYTES or the
+/// deadline, so a truncated last chunk keeps what arrived.
+fn dechunk(mut raw: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    while let Some(eol) = raw.windows(2).position(|w| w == b"\r\n") {
+        let size_line = String::from_utf8_lossy(&raw[..eol]);
+        // Chunk extensions follow a ';'.
+        let size = size_line.split(';').next().unwrap_or("").trim();
+        let Ok(size) = usize::from_str_radix(size, 16) else {
+            break;
+        };
+        if size == 0 {
+            break;
+        }
+        let data = &raw[eol + 2..];
+        let take = size.min(data.len());
+        out.extend_from_slice(&data[..take]);
+        if take < size || data.len() < size + 2 {
+            break;
+        }
+        raw = &data[size + 2..];
+    }
+    out
+}
+
+/// Probe one project's url. None when the project has no url.
+pub fn check_project(p: &Project, timeout: Duration) -> Option<HealthStatus> {
+    let url = p.url.clone()?;
+    let started = Instant::now();
+
+    if url.starts_with("https://") {
+        return Some(HealthStatus {
+            key: p.key.clone(),
+            ok: false,
+            checked: false,
+            status: None,
+            latency_ms: None,
+            body_matched: None,
+            err: Some("https urls are not health checked".to_string()),
+            url,
+        });
+    }
+
+    let status = match http_get(&url, timeout) {
+        Ok(resp) => {
+            let body_matched = p.health_expect.as_ref().map(|needle| resp.body.contains(needle.as_str()));
+            HealthStatus {
+                key: p.key.clone(),
+                ok: resp.status < 400 && body_matched != Some(false),
+                checked: true,
+                status: Some(resp.status),
+                latency_ms: Some(started.elapsed().as_millis() as u64),
+                body_matched,
+                err: None,
+                url,
+            }
+        }
+        Err(e) => HealthStatus {
+            key: p.key.clone(),
+            ok: false,
+            checked: true,
+            status: None,
+            latency_ms: None,
+            body_matched: None,
+            err: Some(e),
+            url,
+        },
+    };
+    Some(status)
+}
+
+/// Check several projects concurrently; each is bounded by `timeout`.
+pub fn check_projects(projects: &[Project], timeout: Duration) -> Vec<HealthStatus> {
+    thread::scope(|s| {
+        let handles: Vec<_> = projects
+            .iter()
+            .map(|p| s.spawn(move || check_project(p, timeout)))
+            .collect();
+        handles.into_iter().filter_map(|h| h.join().ok().flatten()).collect()
+    })
+}
+
+/// Health of every registered project with a url, or only `keys` if given.
+#[tauri::command(async)]
+pub fn o2_health_check(keys: Option<Vec<String>>) -> Result<Vec<HealthStatus>, String> {
+    let mut projects = load_registry()?.projects;
+    if let Some(keys) = keys {
+        projects.retain(|p| keys.contains(&p.key));
+    }
+    Ok(check_projects(&projects, HEALTH_TIMEOUT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    const TIMEOUT: Duration = Duration::from_millis(500);
+
+    fn project(url: &str, expect: Option<&str>) -> Project {
+        serde_json::from_value(serde_json::json!({
+            "key": "stub",
+            "url": url,
+            "healthExpect": expect,
+        }))
+        .unwrap()
+    }
+
+    /// Serve one connection with `response`, or hold it open without
+    /// answering when `response` is None.
+    fn stub(response: Option<&'static str>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/health", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut conn, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = conn.read(&mut buf);
+            match response {
+                Some(r) => {
+                    let _ = conn.write_all(r.as_bytes());
+                }
+                None => thread::sleep(TIMEOUT * 4),
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn ok_response_with_expected_body() {
+        let url = stub(Some("HTTP/1.1 200 OK\r\nContent-Length: 13\r\n\r\nradcontrol ok"));
+        let h = check_project(&project(&url, Some("radcontrol")), TIMEOUT).unwrap();
+        assert!(h.ok && h.checked);
+        assert_eq!(h.status, Some(200));
+        assert_eq!(h.body_matched, Some(true));
+        assert!(h.latency_ms.is_some());
+    }
+
+    #[test]
+    fn expect_mismatch_is_unhealthy() {
+        let url = stub(Some("HTTP/1.1 200 OK\r\n\r\nsome other app"));
+        let h = check_project(&project(&url, Some("radcontrol")), TIMEOUT).unwrap();
+        assert!(!h.ok);
+        assert_eq!(h.status, Some(200));
+        assert_eq!(h.body_matched, Some(false));
+    }
+
+    #[test]
+    fn server_error_is_unhealthy() {
+        let url = stub(Some("HTTP/1.1 503 Service Unavailable\r\n\r\n"));
+        let h = check_project(&project(&url, None), TIMEOUT).unwrap();
+        assert!(!h.ok);
+        assert_eq!(h.status, Some(503));
+    }
+
+    #[test]
+    fn silent_server_times_out() {
+        let url = stub(None);
+        let started = Instant::now();
+        let h = check_project(&project(&url, None), TIMEOUT).unwrap();
+        assert!(!h.ok && h.checked);
+        assert!(h.err.unwrap().starts_with("no response"));
+        assert!(started.elapsed() < TIMEOUT * 2);
+    }
+
+    #[test]
+    fn connection_refused() {
+        let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let h = check_project(&project(&format!("http://{addr}/"), None), TIMEOUT).unwrap();
+        assert!(!h.ok && h.checked);
+        assert!(h.err.unwrap().starts_with("connect failed"));
+    }
+
+    #[test]
+    fn chunked_body_is_decoded() {
+        let url = stub(Some(
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nradco\r\n5;x=y\r\nntrol\r\n0\r\n\r\n",
+        ));
+        let h = check_project(&project(&url, Some("radcontrol")), TIMEOUT).unwrap();
+        assert_eq!(h.body_matched, Some(true));
+        assert_eq!(dechunk(b"4\r\nabcd\r\n4\r\nef"), b"abcdef");
+    }
+
+    #[test]
+    fn https_is_left_unchecked() {
+        let h = check_project(&project("https://example.com/", None), TIMEOUT).unwrap();
+        assert!(!h.checked);
+        assert_eq!(h.status, None);
+    }
+}
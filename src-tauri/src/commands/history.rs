@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+use super::jobs::JobState;
+use super::o2::O2Args;
+
+// Per-stream output kept in a record; the tail is what people look at.
+const MAX_OUTPUT_BYTES: usize = 16 * 1024;
+const DEFAULT_LIST_LIMIT: usize = 100;
+
+/// One finished O2 run, stored as a line of `history.jsonl`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryRecord {
+    pub id: String,
+    pub verb: String,
+    pub project: Option<String>,
+    pub started_at_ms: u64,
+    pub ended_at_ms: u64,
+    pub status: JobState,
+    pub code: i32,
+    pub stdout: String,
+    pub stderr: String,
+    /// Named arguments the verb ran with (see `O2Args`).
+    #[serde(default, skip_serializing_if = "O2Args::is_empty")]
+    pub args: O2Args,
+    #[serde(default)]
+    pub output_truncated: bool,
+    /// What started the run: "ui", "cli", "api", "workflow", ...
+    pub trigger: String,
+}
+
+/// `HistoryRecord` without the captured output, for listings.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySummary {
+    pub id: String,
+    pub verb: String,
+    pub project: Option<String>,
+    pub started_at_ms: u64,
+    pub ended_at_ms: u64,
+    pub status: JobState,
+    pub code: i32,
+    pub trigger: String,
+}
+
+impl From<&HistoryRecord> for HistorySummary {
+    fn from(r: &HistoryRecord) -> Self {
+        HistorySummary {
+            id: r.id.clone(),
+            verb: r.verb.clone(),
+            project: r.project.clone(),
+            started_at_ms: r.started_at_ms,
+            ended_at_ms: r.ended_at_ms,
+            status: r.status,
+            code: r.code,
+            trigger: r.trigger.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryFilter {
+    pub project: Option<String>,
+    pub verb: Option<String>,
+    pub status: Option<JobState>,
+    /// Inclusive bounds on `startedAtMs`.
+    pub since_ms: Option<u64>,
+    pub until_ms: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+impl HistoryFilter {
+    fn matches(&self, r: &HistoryRecord) -> bool {
+        self.project.as_ref().is_none_or(|p| r.project.as_ref() == Some(p))
+            && self.verb.as_ref().is_none_or(|v| &r.verb == v)
+            && self.status.is_none_or(|s| r.status == s)
+            && self.since_ms.is_none_or(|t| r.started_at_ms >= t)
+            && self.until_ms.is_none_or(|t| r.started_at_ms <= t)
+    }
+}
+
+/// Keep the last `max` bytes of `s`, cut on a char boundary.
+pub fn tail_bytes(s: &str, max: usize) -> (String, bool) {
+    if s.len() <= max {
+        return (s.to_string(), false);
+    }
+    let mut start = s.len() - max;
+    while !s.is_char_boundary(start) {
+        start += 1;
+    }
+    (s[start..].to_string(), true)
+}
+
+/// Append-only run history under the app data dir. Managed as Tauri state.
+#[derive(Clone)]
+pub struct HistoryStore {
+    path: PathBuf,
+    lock: Arc<Mutex<()>>,
+}
+
+impl HistoryStore {
+    pub fn new(dir: &Path) -> Self {
+        HistoryStore {
+            path: dir.join("history.jsonl"),
+            lock: Arc::default(),
+        }
+    }
+
+    /// Store a run, truncating its output to the last MAX_OUTPUT_BYTES.
+    pub fn append(&self, mut record: HistoryRecord) -> Result<(), String> {
+        let (stdout, out_cut) = tail_bytes(&record.stdout, MAX_OUTPUT_BYTES);
+        let (stderr, err_cut) = tail_bytes(&record.stderr, MAX_OUTPUT_BYTES);
+        record.stdout = stdout;
+        record.stderr = stderr;
+        record.output_truncated |= out_cut || err_cut;
+
+        let mut line = serde_json::to_string(&record).map_err(|e| format!("Failed to serialize history record: {e}"))?;
+        line.push('\n');
+
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+        }
+        let mut f = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open {}: {e}", self.path.display()))?;
+        f.write_all(line.as_bytes())
+            .map_err(|e| format!("Failed to write {}: {e}", self.path.display()))
+    }
+
+    /// Every parseable record, oldest first. Corrupt lines are skipped.
+    fn read_all(&self) -> Result<Vec<HistoryRecord>, String> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let f = match fs::File::open(&self.path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to open {}: {e}", self.path.display())),
+        };
+
+        // Split on raw bytes so a torn or non-UTF-8 line only drops itself;
+        // a real read error still ends the scan.
+        Ok(BufReader::new(f)
+            .split(b'\n')
+            .map_while(Result::ok)
+            .filter_map(|l| serde_json::from_slice::<HistoryRecord>(&l).ok())
+            .collect())
+    }
+
+    /// Matching runs, newest first.
+    pub fn list(&self, filter: &HistoryFilter) -> Result<Vec<HistorySummary>, String> {
+        let limit = filter.limit.unwrap_or(DEFAULT_LIST_LIMIT);
+        Ok(self
+            .read_all()?
+            .iter()
+            .rev()
+            .filter(|r| filter.matches(r))
+            .take(limit)
+            .map(HistorySummary::from)
+            .collect())
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<HistoryRecord>, String> {
+        Ok(self.read_all()?.into_iter().rev().find(|r| r.id == id))
+    }
+}
+
+#[tauri::command(async)]
+pub fn o2_history_list(
+    history: State<'_, HistoryStore>,
+    filter: Option<HistoryFilter>,
+) -> Result<Vec<HistorySummary>, String> {
+    history.list(&filter.unwrap_or_default())
+}
+
+#[tauri::command(async)]
+pub fn o2_history_get(history: State<'_, HistoryStore>, id: String) -> Result<HistoryRecord, String> {
+    history
+        .get(id.trim())?
+        .ok_or_else(|| format!("no history record: {}", id.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, project: Option<&str>, verb: &str, status: JobState, started_at_ms: u64) -> HistoryRecord {
+        HistoryRecord {
+            id: id.to_string(),
+            verb: verb.to_string(),
+            project: project.map(str::to_string),
+            started_at_ms,
+            ended_at_ms: started_at_ms + 10,
+            status,
+            code: 0,
+            stdout: String::new(),
+            stderr: String::new(),
+            args: O2Args::new(),
+            output_truncated: false,
+            trigger: "ui".to_string(),
+        }
+    }
+
+    #[test]
+    fn tail_bytes_cuts_on_char_boundaries() {
+        assert_eq!(tail_bytes("abc", 3), ("abc".to_string(), false));
+        assert_eq!(tail_bytes("abcdef", 2), ("ef".to_string(), true));
+        // "é" and "€" are 2 and 3 bytes; never split one.
+        assert_eq!(tail_bytes("aé", 1), ("".to_string(), true));
+        assert_eq!(tail_bytes("aé", 2), ("é".to_string(), true));
+        assert_eq!(tail_bytes("x€y", 3), ("y".to_string(), true));
+        assert_eq!(tail_bytes("x€y", 4), ("€y".to_string(), true));
+        assert_eq!(tail_bytes("", 0), ("".to_string(), false));
+    }
+
+    #[test]
+    fn filter_matches_every_given_field() {
+        let r = record("1", Some("tbis"), "tbis.commit", JobState::Failed, 1000);
+        assert!(HistoryFilter::default().matches(&r));
+
+        let f = |edit: fn(&mut HistoryFilter)| {
+            let mut f = HistoryFilter::default();
+            edit(&mut f);
+            f.matches(&r)
+        };
+        assert!(f(|f| f.project = Some("tbis".to_string())));
+        assert!(!f(|f| f.project = Some("dqotd".to_string())));
+        assert!(f(|f| f.verb = Some("tbis.commit".to_string())));
+        assert!(!f(|f| f.verb = Some("tbis".to_string())));
+        assert!(f(|f| f.status = Some(JobState::Failed)));
+        assert!(!f(|f| f.status = Some(JobState::Succeeded)));
+        // Time bounds are inclusive.
+        assert!(f(|f| f.since_ms = Some(1000)));
+        assert!(!f(|f| f.since_ms = Some(1001)));
+        assert!(f(|f| f.until_ms = Some(1000)));
+        assert!(!f(|f| f.until_ms = Some(999)));
+
+        let unowned = record("2", None, "o2.sync", JobState::Succeeded, 5);
+        let by_project = HistoryFilter { project: Some("tbis".to_string()), ..Default::default() };
+        assert!(!by_project.matches(&unowned));
+    }
+
+    #[test]
+    fn corrupt_lines_do_not_hide_later_records() {
+        let dir = std::env::temp_dir().join(format!("radcontrol-history-{}", std::process::id()));
+        let store = HistoryStore::new(&dir);
+        store.append(record("a", None, "v", JobState::Succeeded, 1)).unwrap();
+        let mut f = OpenOptions::new().append(true).open(&store.path).unwrap();
+        f.write_all(b"{\"id\": \"torn\n\xff\xfe not utf-8\n").unwrap();
+        store.append(record("b", None, "v", JobState::Succeeded, 2)).unwrap();
+
+        let ids: Vec<String> = store.list(&HistoryFilter::default()).unwrap().into_iter().map(|s| s.id).collect();
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(ids, ["b", "a"]);
+    }
+}
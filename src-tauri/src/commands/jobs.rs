@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+use crate::process;
+
+// Finished jobs kept around for o2_list_jobs; older ones are dropped.
+const KEEP_FINISHED: usize = 200;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+    TimedOut,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    pub id: String,
+    pub verb: String,
+    pub pid: Option<u32>,
+    pub started_at_ms: u64,
+    pub ended_at_ms: Option<u64>,
+    pub state: JobState,
+}
+
+struct JobEntry {
+    info: JobInfo,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Every O2 job RadControl has started, running or recently finished.
+/// Managed as Tauri state; cheap to clone into worker threads.
+#[derive(Clone, Default)]
+pub struct JobTable {
+    jobs: Arc<Mutex<HashMap<String, JobEntry>>>,
+}
+
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl JobTable {
+    /// Record a new running job and hand back its cancel flag.
+    pub fn register(&self, id: &str, verb: &str) -> Arc<AtomicBool> {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let entry = JobEntry {
+            info: JobInfo {
+                id: id.to_string(),
+                verb: verb.to_string(),
+                pid: None,
+                started_at_ms: now_ms(),
+                ended_at_ms: None,
+                state: JobState::Running,
+            },
+            cancel: cancel.clone(),
+        };
+
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        jobs.insert(id.to_string(), entry);
+        cancel
+    }
+
+    pub fn set_pid(&self, id: &str, pid: u32) {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(j) = jobs.get_mut(id) {
+            j.info.pid = Some(pid);
+        }
+    }
+
+    pub fn finish(&self, id: &str, state: JobState) {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(j) = jobs.get_mut(id) {
+            j.info.state = state;
+            j.info.ended_at_ms = Some(now_ms());
+        }
+        prune_finished(&mut jobs);
+    }
+
+    pub fn get(&self, id: &str) -> Option<JobInfo> {
+        let jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        jobs.get(id).map(|j| j.info.clone())
+    }
+
+    /// Newest first.
+    pub fn list(&self) -> Vec<JobInfo> {
+        let jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out: Vec<JobInfo> = jobs.values().map(|j| j.info.clone()).collect();
+        out.sort_by(|a, b| b.started_at_ms.cmp(&a.started_at_ms).then(b.id.cmp(&a.id)));
+        out
+    }
+
+    /// Flag the job as cancelled and SIGTERM its process group. The runner
+    /// escalates to SIGKILL if the group outlives the grace period.
+    pub fn cancel(&self, id: &str) -> Result<JobInfo, String> {
+        let jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        let j = jobs.get(id).ok_or_else(|| format!("unknown job: {id}"))?;
+
+        if j.info.state != JobState::Running {
+            return Err(format!("job {id} is not running"));
+        }
+
+        j.cancel.store(true, Ordering::SeqCst);
+        if let Some(pid) = j.info.pid {
+            process::signal_group(pid, process::SIGTERM);
+        }
+        Ok(j.info.clone())
+    }
+}
+
+fn prune_finished(jobs: &mut HashMap<String, JobEntry>) {
+    let mut finished: Vec<(u64, String)> = jobs
+        .values()
+        .filter(|j| j.info.state != JobState::Running)
+        .map(|j| (j.info.started_at_ms, j.info.id.clone()))
+        .collect();
+
+    if finished.len() <= KEEP_FINISHED {
+        return;
+    }
+
+    finished.sort();
+    let excess = finished.len() - KEEP_FINISHED;
+    for (_, id) in finished.into_iter().take(excess) {
+        jobs.remove(&id);
+    }
+}
+
+#[tauri::command]
+pub fn o2_list_jobs(jobs: State<'_, JobTable>) -> Vec<JobInfo> {
+    jobs.list()
+}
+
+#[tauri::command]
+pub fn o2_cancel(jobs: State<'_, JobTable>, job_id: String) -> Result<JobInfo, String> {
+    jobs.cancel(job_id.trim())
+}
@@ -0,0 +1,416 @@
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, State};
+
+use super::jobs::now_ms;
+
+pub const LOG_LINE_EVENT: &str = "log-line";
+
+// Under the app data dir: <source>.log, rotated to <source>.log.1 ... .N
+const LOGS_DIR: &str = "logs";
+const FILE_MAX_BYTES: u64 = 5 * 1024 * 1024;
+const KEEP_ROTATED: usize = 3;
+
+// In-memory tail per source; whichever limit is hit first applies.
+const RING_MAX_LINES: usize = 5000;
+const RING_MAX_BYTES: usize = 2 * 1024 * 1024;
+
+const DEFAULT_TAIL_LINES: usize = 200;
+const DEFAULT_SEARCH_LIMIT: usize = 500;
+// Compiled size cap for user-supplied patterns.
+const SEARCH_REGEX_SIZE_LIMIT: usize = 1024 * 1024;
+
+/// One captured output line, stored as a line of JSON in the log file.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LogLine {
+    /// Increases by one per line within a source, across restarts.
+    pub seq: u64,
+    pub at_ms: u64,
+    /// "stdout", "stderr", or "supervisor" for lifecycle notes.
+    pub stream: String,
+    pub line: String,
+    /// Set for lines from an O2 job.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+}
+
+/// Emitted as `log-line` for sources someone follows (see o2_logs_follow).
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LogLineEvent {
+    pub source: String,
+    #[serde(flatten)]
+    pub line: LogLine,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LogSearchResult {
+    /// Oldest first; the most recent `limit` matches when truncated.
+    pub matches: Vec<LogLine>,
+    pub truncated: bool,
+}
+
+struct Ring {
+    lines: VecDeque<LogLine>,
+    bytes: usize,
+    next_seq: u64,
+    file: Option<File>,
+    file_bytes: u64,
+}
+
+impl Ring {
+    fn push(&mut self, line: LogLine) {
+        self.bytes += line.line.len();
+        self.lines.push_back(line);
+        while self.lines.len() > RING_MAX_LINES || self.bytes > RING_MAX_BYTES {
+            match self.lines.pop_front() {
+                Some(old) => self.bytes -= old.line.len(),
+                None => break,
+            }
+        }
+    }
+}
+
+/// Output of supervised projects and O2 jobs, kept per source (a project
+/// key, or "o2" for verbs that don't belong to one) as a bounded in-memory
+/// ring backed by rotating files. Managed as Tauri state.
+#[derive(Clone)]
+pub struct LogStore {
+    dir: PathBuf,
+    rings: Arc<Mutex<HashMap<String, Ring>>>,
+    followed: Arc<Mutex<HashSet<String>>>,
+    /// None for the CLI, which writes the same files but has no one to
+    /// emit `log-line` to.
+    app: Option<AppHandle>,
+}
+
+/// File-name-safe form of a source name: `[A-Za-z0-9_-]` as is, every other
+/// byte as `%XX`, so distinct sources never share a file.
+pub(super) fn file_stem(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    for b in source.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+/// Inverse of `file_stem`; None for names it can't have produced.
+fn source_name(stem: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(stem.len());
+    let mut rest = stem.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn read_lines(path: &Path) -> Vec<LogLine> {
+    let Ok(f) = File::open(path) else {
+        return Vec::new();
+    };
+    // A torn or non-UTF-8 line only drops itself.
+    BufReader::new(f)
+        .split(b'\n')
+        .map_while(Result::ok)
+        .filter_map(|l| serde_json::from_slice::<LogLine>(&l).ok())
+        .collect()
+}
+
+impl LogStore {
+    pub fn new(data_dir: &Path, app: AppHandle) -> Self {
+        LogStore {
+            dir: data_dir.join(LOGS_DIR),
+            rings: Arc::default(),
+            followed: Arc::default(),
+            app: Some(app),
+        }
+    }
+
+    /// A store that writes the log files but emits no events.
+    pub fn headless(data_dir: &Path) -> Self {
+        LogStore {
+            dir: data_dir.join(LOGS_DIR),
+            rings: Arc::default(),
+            followed: Arc::default(),
+            app: None,
+        }
+    }
+
+    /// `<source>.log` for n = 0, `<source>.log.<n>` for rotated files.
+    fn file_path(&self, source: &str, n: usize) -> PathBuf {
+        let stem = file_stem(source);
+        match n {
+            0 => self.dir.join(format!("{stem}.log")),
+            n => self.dir.join(format!("{stem}.log.{n}")),
+        }
+    }
+
+    /// Seed a ring from the newest files so tails survive an app restart.
+    fn load_ring(&self, source: &str) -> Ring {
+        let mut ring = Ring {
+            lines: VecDeque::new(),
+            bytes: 0,
+            next_seq: 0,
+            file: None,
+            file_bytes: fs::metadata(self.file_path(source, 0)).map(|m| m.len()).unwrap_or(0),
+        };
+        let mut older: Vec<LogLine> = Vec::new();
+        for n in 0..=KEEP_ROTATED {
+            let mut lines = read_lines(&self.file_path(source, n));
+            lines.append(&mut older);
+            older = lines;
+            if older.len() >= RING_MAX_LINES {
+                break;
+            }
+        }
+        ring.next_seq = older.last().map(|l| l.seq + 1).unwrap_or(0);
+        for line in older {
+            ring.push(line);
+        }
+        ring
+    }
+
+    fn rotate(&self, source: &str, ring: &mut Ring) {
+        ring.file = None;
+        for n in (1..KEEP_ROTATED).rev() {
+            let _ = fs::rename(self.file_path(source, n), self.file_path(source, n + 1));
+        }
+        let _ = fs::rename(self.file_path(source, 0), self.file_path(source, 1));
+        ring.file_bytes = 0;
+    }
+
+    fn write_line(&self, source: &str, ring: &mut Ring, line: &LogLine) {
+        let Ok(mut json) = serde_json::to_string(line) else {
+            return;
+        };
+        json.push('\n');
+
+        if ring.file_bytes > 0 && ring.file_bytes + json.len() as u64 > FILE_MAX_BYTES {
+            self.rotate(source, ring);
+        }
+        if ring.file.is_none() {
+            let _ = fs::create_dir_all(&self.dir);
+            ring.file = OpenOptions::new().create(true).append(true).open(self.file_path(source, 0)).ok();
+        }
+        // A full disk shouldn't take the process down with it; the ring
+        // still has the line.
+        if let Some(f) = ring.file.as_mut() {
+            if f.write_all(json.as_bytes()).is_ok() {
+                ring.file_bytes += json.len() as u64;
+            }
+        }
+    }
+
+    /// Run `f` on the ring for `source`, loading it first if needed. Loading
+    /// reads files, so it happens outside the lock that every source shares;
+    /// if two callers race, the first ring inserted wins.
+    fn with_ring<R>(&self, source: &str, f: impl FnOnce(&mut Ring) -> R) -> R {
+        let loaded = self.rings.lock().unwrap_or_else(|e| e.into_inner()).contains_key(source);
+        let ring = (!loaded).then(|| self.load_ring(source));
+
+        let mut rings = self.rings.lock().unwrap_or_else(|e| e.into_inner());
+        let ring = match ring {
+            Some(ring) => rings.entry(source.to_string()).or_insert(ring),
+            None => rings.get_mut(source).expect("rings are never removed"),
+        };
+        f(ring)
+    }
+
+    pub fn append(&self, source: &str, stream: &str, line: &str, job_id: Option<&str>) {
+        let line = self.with_ring(source, |ring| {
+            let line = LogLine {
+                seq: ring.next_seq,
+                at_ms: now_ms(),
+                stream: stream.to_string(),
+                line: line.trim_end_matches(['\n', '\r']).to_string(),
+                job_id: job_id.map(str::to_string),
+            };
+            ring.next_seq += 1;
+            self.write_line(source, ring, &line);
+            ring.push(line.clone());
+            line
+        });
+
+        let Some(app) = &self.app else {
+            return;
+        };
+        let followed = self.followed.lock().unwrap_or_else(|e| e.into_inner()).contains(source);
+        if followed {
+            let _ = app.emit(
+                LOG_LINE_EVENT,
+                LogLineEvent {
+                    source: source.to_string(),
+                    line,
+                },
+            );
+        }
+    }
+
+    /// Last `n` lines, oldest first.
+    pub fn tail(&self, source: &str, n: usize) -> Vec<LogLine> {
+        self.with_ring(source, |ring| {
+            let lines = &ring.lines;
+            lines.iter().skip(lines.len().saturating_sub(n)).cloned().collect()
+        })
+    }
+
+    /// Ring lines after `seq` (all of them for None), oldest first.
+    pub fn since(&self, source: &str, seq: Option<u64>) -> Vec<LogLine> {
+        self.with_ring(source, |ring| {
+            ring.lines.iter().filter(|l| seq.is_none_or(|s| l.seq > s)).cloned().collect()
+        })
+    }
+
+    pub fn set_followed(&self, source: &str, follow: bool) {
+        let mut followed = self.followed.lock().unwrap_or_else(|e| e.into_inner());
+        if follow {
+            followed.insert(source.to_string());
+        } else {
+            followed.remove(source);
+        }
+    }
+
+    /// Lines matching `pattern` at or after `since_ms`, searched across the
+    /// rotated files rather than just the ring.
+    pub fn search(
+        &self,
+        source: &str,
+        pattern: &str,
+        since_ms: Option<u64>,
+        limit: usize,
+    ) -> Result<LogSearchResult, String> {
+        let re = RegexBuilder::new(pattern)
+            .size_limit(SEARCH_REGEX_SIZE_LIMIT)
+            .build()
+            .map_err(|e| format!("invalid pattern: {e}"))?;
+
+        let mut matches = VecDeque::new();
+        let mut truncated = false;
+        for n in (0..=KEEP_ROTATED).rev() {
+            for l in read_lines(&self.file_path(source, n)) {
+                if since_ms.is_some_and(|t| l.at_ms < t) || !re.is_match(&l.line) {
+                    continue;
+                }
+                if matches.len() == limit {
+                    matches.pop_front();
+                    truncated = true;
+                }
+                matches.push_back(l);
+            }
+        }
+        Ok(LogSearchResult {
+            matches: matches.into(),
+            truncated,
+        })
+    }
+
+    /// Sources with a log file on disk, sorted.
+    pub fn sources(&self) -> Vec<String> {
+        let Ok(rd) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut out: Vec<String> = rd
+            .filter_map(|e| e.ok())
+            .filter_map(|e| source_name(e.file_name().to_str()?.strip_suffix(".log")?))
+            .collect();
+        out.sort();
+        out
+    }
+}
+
+#[tauri::command]
+pub fn o2_logs_sources(logs: State<'_, LogStore>) -> Vec<String> {
+    logs.sources()
+}
+
+#[tauri::command(async)]
+pub fn o2_logs_tail(logs: State<'_, LogStore>, source: String, n: Option<usize>) -> Vec<LogLine> {
+    logs.tail(source.trim(), n.unwrap_or(DEFAULT_TAIL_LINES))
+}
+
+/// Start or stop `log-line` events for `source`. Starting returns the last
+/// `backlog` lines so the listener has context for what follows.
+#[tauri::command(async)]
+pub fn o2_logs_follow(
+    logs: State<'_, LogStore>,
+    source: String,
+    follow: bool,
+    backlog: Option<usize>,
+) -> Vec<LogLine> {
+    let source = source.trim();
+    logs.set_followed(source, follow);
+    if follow {
+        logs.tail(source, backlog.unwrap_or(DEFAULT_TAIL_LINES))
+    } else {
+        Vec::new()
+    }
+}
+
+#[tauri::command(async)]
+pub fn o2_logs_search(
+    logs: State<'_, LogStore>,
+    source: String,
+    pattern: String,
+    since_ms: Option<u64>,
+    limit: Option<usize>,
+) -> Result<LogSearchResult, String> {
+    logs.search(source.trim(), &pattern, since_ms, limit.unwrap_or(DEFAULT_SEARCH_LIMIT).max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_stems_are_distinct_and_reversible() {
+        assert_eq!(file_stem("tbis"), "tbis");
+        assert_eq!(file_stem("rad_con-2"), "rad_con-2");
+        assert_ne!(file_stem("a/b"), file_stem("a_b"));
+        assert_ne!(file_stem("a.b"), file_stem("a_b"));
+        assert_eq!(file_stem("../x"), "%2E%2E%2Fx");
+        for source in ["tbis", "a/b", "a.b", "a b", "é", "%41"] {
+            assert_eq!(source_name(&file_stem(source)).as_deref(), Some(source));
+        }
+        assert_eq!(source_name("bad%4"), None);
+        assert_eq!(source_name("bad%zz"), None);
+    }
+
+    #[test]
+    fn rings_reload_from_disk_past_corrupt_lines() {
+        let dir = std::env::temp_dir().join(format!("radcontrol-logs-{}", std::process::id()));
+        let store = LogStore::headless(&dir);
+        store.append("a/b", "stdout", "one\n", None);
+        let mut f = OpenOptions::new().append(true).open(store.file_path("a/b", 0)).unwrap();
+        f.write_all(b"{\"seq\": \n\xff\xfe\n").unwrap();
+        store.append("a/b", "stderr", "two", Some("o2-1"));
+        store.append("a_b", "stdout", "other", None);
+
+        let reloaded = LogStore::headless(&dir);
+        let lines = reloaded.tail("a/b", 10);
+        let sources = reloaded.sources();
+        let _ = fs::remove_dir_all(&dir);
+
+        let text: Vec<(u64, &str)> = lines.iter().map(|l| (l.seq, l.line.as_str())).collect();
+        assert_eq!(text, [(0, "one"), (1, "two")]);
+        assert_eq!(lines[1].job_id.as_deref(), Some("o2-1"));
+        assert_eq!(sources, ["a/b", "a_b"]);
+    }
+}
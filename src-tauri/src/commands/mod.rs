@@ -1,2 +1,19 @@
+pub mod commit;
+pub mod confirm;
+pub mod git;
+pub mod health;
+pub mod history;
+pub mod jobs;
+pub mod logs;
 pub mod o2;
+pub mod panic;
+pub mod ports;
 pub mod registry;
+pub mod repo_index;
+pub mod resources;
+pub mod snapshot;
+pub mod status;
+pub mod supervisor;
+pub mod timeouts;
+pub mod verbs;
+pub mod workflows;
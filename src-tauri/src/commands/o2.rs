@@ -1,56 +1,546 @@
 use serde::Serialize;
-use std::process::Command;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, State};
 
-#[derive(Serialize)]
+use super::confirm::ConfirmTokens;
+use super::history::{HistoryRecord, HistoryStore};
+use super::jobs::{now_ms, JobState, JobTable};
+use super::logs::LogStore;
+use super::timeouts::timeout_for_verb;
+use super::verbs::{allowed_verbs, check_args, load_verbs, VerbInfo};
+use crate::process;
+
+pub const O2_OUTPUT_EVENT: &str = "o2-output";
+pub const O2_FINISHED_EVENT: &str = "o2-finished";
+
+const POLL: Duration = Duration::from_millis(50);
+// SIGTERM -> SIGKILL escalation window after o2_cancel or a timeout.
+const TERM_GRACE: Duration = Duration::from_secs(3);
+const DRAIN_AFTER_EXIT: Duration = Duration::from_secs(1);
+
+/// Named arguments for a verb. Each one reaches run_o2.sh as its own
+/// environment variable, `O2_ARG_<NAME>`, never as part of the verb string.
+pub type O2Args = BTreeMap<String, String>;
+
+/// One checked verb invocation (see `check_verb`).
+pub struct O2Call {
+  pub job_id: String,
+  pub verb: String,
+  pub args: O2Args,
+  /// What started the run, kept in history: "ui", "cli", "api", "workflow".
+  pub trigger: &'static str,
+  /// Overrides the per-verb timeout from `timeouts.rs`.
+  pub timeout: Option<Duration>,
+  /// Overrides `$O2_ROOT` for locating `scripts/run_o2.sh`.
+  pub root: Option<String>,
+}
+
+impl O2Call {
+  pub fn new(verb: String, args: O2Args, trigger: &'static str) -> Self {
+    O2Call {
+      job_id: next_job_id(),
+      verb,
+      args,
+      trigger,
+      timeout: None,
+      root: None,
+    }
+  }
+}
+
+#[derive(Serialize, Clone)]
 pub struct RunO2Result {
   pub ok: bool,
   pub code: i32,
   pub stdout: String,
   pub stderr: String,
+  /// Set when the job was stopped through o2_cancel.
+  pub cancelled: bool,
+  /// Set when the job exceeded its per-verb timeout and was killed.
+  pub timed_out: bool,
+  pub elapsed_ms: u64,
+  /// Set when the verb was rejected before anything was spawned.
+  pub error: Option<O2Error>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum O2ErrorKind {
+  EmptyVerb,
+  VerbNotAllowed,
+  /// Not on the allowlist, which has entries that failed to load.
+  CatalogUnavailable,
+  /// Arguments didn't match the verb's declared params.
+  InvalidArgs,
+  /// Destructive verb run without a token from o2_prepare_verb.
+  ConfirmationRequired,
+  /// Token unknown, expired, used, or for another call; or wrong phrase.
+  ConfirmationInvalid,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct O2Error {
+  pub kind: O2ErrorKind,
+  pub message: String,
+  pub verb: String,
+}
+
+impl RunO2Result {
+  fn rejected(err: O2Error) -> Self {
+    RunO2Result {
+      ok: false,
+      code: 1,
+      stdout: "".to_string(),
+      stderr: err.message.clone(),
+      cancelled: false,
+      timed_out: false,
+      elapsed_ms: 0,
+      error: Some(err),
+    }
+  }
+}
+
+/// One line of child output, emitted as `o2-output` while the job runs.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct O2OutputEvent {
+  pub job_id: String,
+  pub stream: &'static str,
+  pub line: String,
+}
+
+/// Emitted once as `o2-finished`; carries the same fields as `RunO2Result`.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct O2FinishedEvent {
+  pub job_id: String,
+  pub verb: String,
+  #[serde(flatten)]
+  pub result: RunO2Result,
 }
 
 fn o2_root() -> String {
   std::env::var("O2_ROOT").unwrap_or_else(|_| format!("{}/dev/o2", std::env::var("HOME").unwrap_or_else(|_| "/home/chris".to_string())))
 }
 
-fn run_o2_command(arg: &str) -> RunO2Result {
+/// Trim the verb, make sure it is on the allowlist (see `verbs.rs`) and
+/// validate `args` against the params it declares.
+pub fn check_verb(raw: &str, args: Option<&Map<String, Value>>) -> Result<(VerbInfo, O2Args), O2Error> {
+  // Defensive trim; keep it as one argument.
+  let verb = raw.trim().to_string();
+  if verb.is_empty() {
+    return Err(O2Error {
+      kind: O2ErrorKind::EmptyVerb,
+      message: "empty verb".to_string(),
+      verb,
+    });
+  }
+
+  let loaded = load_verbs();
+  let Some(info) = loaded.verbs.into_iter().find(|v| v.verb == verb) else {
+    // The verb may be one of the entries that failed to load; say so rather
+    // than just "not allowed".
+    if !loaded.errors.is_empty() {
+      let errors: Vec<String> = loaded.errors.into_iter().map(|e| e.message).collect();
+      return Err(O2Error {
+        kind: O2ErrorKind::CatalogUnavailable,
+        message: format!("verb not allowed: {verb} (verb list incomplete: {})", errors.join("; ")),
+        verb,
+      });
+    }
+    return Err(O2Error {
+      kind: O2ErrorKind::VerbNotAllowed,
+      message: format!("verb not allowed: {verb}"),
+      verb,
+    });
+  };
+
+  let args = check_args(&info.params, args.unwrap_or(&Map::new())).map_err(|e| O2Error {
+    kind: O2ErrorKind::InvalidArgs,
+    message: format!("invalid arguments for {verb}: {e}"),
+    verb: verb.clone(),
+  })?;
+
+  Ok((info, args))
+}
+
+fn next_job_id() -> String {
+  static SEQ: AtomicU64 = AtomicU64::new(1);
+  let ms = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_millis())
+    .unwrap_or(0);
+  format!("o2-{}-{}", ms, SEQ.fetch_add(1, Ordering::Relaxed))
+}
+
+fn spawn_o2(root: &str, arg: &str, args: &O2Args) -> std::io::Result<Child> {
   // We only ever call: bash <O2_ROOT>/scripts/run_o2.sh "<verb>"
   // No freeform shell; arg is treated as a single verb string.
-  let root = o2_root();
   let script = format!("{}/scripts/run_o2.sh", root);
 
-  let out = Command::new("bash")
+  Command::new("bash")
     .arg(script)
     .arg(arg)
-    .output();
-
-  match out {
-    Ok(o) => RunO2Result {
-      ok: o.status.success(),
-      code: o.status.code().unwrap_or(1),
-      stdout: String::from_utf8_lossy(&o.stdout).to_string(),
-      stderr: String::from_utf8_lossy(&o.stderr).to_string(),
+    .envs(args.iter().map(|(k, v)| (format!("O2_ARG_{}", k.to_ascii_uppercase()), v)))
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    // Own process group, so cancellation reaches everything the script starts.
+    .process_group(0)
+    .spawn()
+}
+
+fn forward_lines<R: Read + Send + 'static>(
+  reader: R,
+  stream: &'static str,
+  tx: mpsc::Sender<(&'static str, String)>,
+) -> thread::JoinHandle<()> {
+  thread::spawn(move || {
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    loop {
+      buf.clear();
+      match reader.read_until(b'\n', &mut buf) {
+        Ok(0) | Err(_) => break,
+        Ok(_) => {
+          let line = String::from_utf8_lossy(&buf).to_string();
+          if tx.send((stream, line)).is_err() {
+            break;
+          }
+        }
+      }
+    }
+  })
+}
+
+/// Runs the verb with piped output, calling `on_line` for every stdout/stderr
+/// line as it arrives. Lines are passed without their trailing newline; the
+/// returned result still holds the full, unmodified output.
+///
+/// The job is tracked in `jobs` under `call.job_id` so it can be listed and
+/// cancelled while it runs, and its process group is killed once the call's
+/// timeout (default: the verb's, see `timeouts.rs`) elapses. `call.args` are
+/// exported to the script as `O2_ARG_*` variables.
+fn run_o2_job<F>(jobs: &JobTable, call: &O2Call, mut on_line: F) -> RunO2Result
+where
+  F: FnMut(&'static str, &str),
+{
+  let (job_id, arg) = (call.job_id.as_str(), call.verb.as_str());
+  let started = Instant::now();
+  let timeout = call.timeout.unwrap_or_else(|| timeout_for_verb(arg));
+  let cancel = jobs.register(job_id, arg);
+
+  let root = call.root.clone().unwrap_or_else(o2_root);
+  let mut child = match spawn_o2(&root, arg, &call.args) {
+    Ok(c) => c,
+    Err(e) => {
+      jobs.finish(job_id, JobState::Failed);
+      return RunO2Result {
+        ok: false,
+        code: 1,
+        stdout: "".to_string(),
+        stderr: format!("failed to spawn run_o2: {}", e),
+        cancelled: false,
+        timed_out: false,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        error: None,
+      };
+    }
+  };
+  let pgid = child.id();
+  jobs.set_pid(job_id, pgid);
+
+  let (tx, rx) = mpsc::channel();
+  let mut readers = Vec::new();
+  if let Some(out) = child.stdout.take() {
+    readers.push(forward_lines(out, "stdout", tx.clone()));
+  }
+  if let Some(err) = child.stderr.take() {
+    readers.push(forward_lines(err, "stderr", tx.clone()));
+  }
+  drop(tx);
+
+  let mut stdout = String::new();
+  let mut stderr = String::new();
+  let mut status = None;
+  let mut wait_err = None;
+  let mut exited_at: Option<Instant> = None;
+  let mut cancel_seen: Option<Instant> = None;
+  let mut timed_out = false;
+  let mut term_sent: Option<Instant> = None;
+  let mut killed = false;
+
+  loop {
+    match rx.recv_timeout(POLL) {
+      Ok((stream, line)) => {
+        on_line(stream, line.trim_end_matches(['\n', '\r']));
+        if stream == "stdout" {
+          stdout.push_str(&line);
+        } else {
+          stderr.push_str(&line);
+        }
+        // Fall through: a verb that never stops printing must still see
+        // cancel, its timeout and its own exit.
+      }
+      Err(mpsc::RecvTimeoutError::Disconnected) => {
+        if status.is_some() || wait_err.is_some() {
+          break;
+        }
+        // Pipes closed but the child is still alive; recv no longer blocks.
+        thread::sleep(POLL);
+      }
+      Err(mpsc::RecvTimeoutError::Timeout) => {}
+    }
+
+    if status.is_none() && term_sent.is_none() {
+      if cancel.load(Ordering::SeqCst) {
+        cancel_seen = Some(Instant::now());
+      } else if started.elapsed() >= timeout {
+        timed_out = true;
+      }
+      if cancel_seen.is_some() || timed_out {
+        term_sent = Some(Instant::now());
+        process::signal_group(pgid, process::SIGTERM);
+      }
+    }
+    if let Some(t) = term_sent {
+      if !killed && t.elapsed() >= TERM_GRACE {
+        killed = true;
+        process::signal_group(pgid, process::SIGKILL);
+      }
+    }
+
+    if status.is_none() && wait_err.is_none() {
+      match child.try_wait() {
+        Ok(Some(s)) => {
+          status = Some(s);
+          exited_at = Some(Instant::now());
+        }
+        Ok(None) => {}
+        Err(e) => {
+          wait_err = Some(e);
+          exited_at = Some(Instant::now());
+        }
+      }
+    }
+
+    // A background process started by the verb can hold our pipes open
+    // after the script itself has exited; don't wait on it forever.
+    if exited_at.is_some_and(|t| t.elapsed() >= DRAIN_AFTER_EXIT) {
+      break;
+    }
+  }
+  drop(readers);
+
+  let cancelled = cancel_seen.is_some();
+  if timed_out {
+    stderr.push_str(&format!("run_o2: timed out after {}s\n", timeout.as_secs()));
+  }
+  let elapsed_ms = started.elapsed().as_millis() as u64;
+  let result = match (status, wait_err) {
+    (Some(s), _) => RunO2Result {
+      ok: s.success() && !cancelled && !timed_out,
+      code: s.code().unwrap_or(1),
+      stdout,
+      stderr,
+      cancelled,
+      timed_out,
+      elapsed_ms,
+      error: None,
     },
-    Err(e) => RunO2Result {
+    (None, Some(e)) => RunO2Result {
       ok: false,
       code: 1,
-      stdout: "".to_string(),
-      stderr: format!("failed to spawn run_o2: {}", e),
+      stdout,
+      stderr: format!("{}failed to wait for run_o2: {}", stderr, e),
+      cancelled,
+      timed_out,
+      elapsed_ms,
+      error: None,
     },
+    (None, None) => unreachable!("run_o2 loop exits only after the child is reaped"),
+  };
+
+  let state = if result.cancelled {
+    JobState::Cancelled
+  } else if result.timed_out {
+    JobState::TimedOut
+  } else if result.ok {
+    JobState::Succeeded
+  } else {
+    JobState::Failed
+  };
+  jobs.finish(job_id, state);
+
+  result
+}
+
+/// Registry project a verb was derived from, if any.
+fn project_for_verb(verb: &str) -> Option<String> {
+  allowed_verbs()
+    .into_iter()
+    .find(|v| v.verb == verb)
+    .and_then(|v| v.project)
+}
+
+/// Log source a verb's output is kept under by `run_o2_recorded`.
+pub fn log_source_for_verb(verb: &str) -> String {
+  project_for_verb(verb).unwrap_or_else(|| "o2".to_string())
+}
+
+/// `run_o2_job` plus a history record, with output kept in the log of the
+/// verb's project (or "o2"; see `logs.rs`). History write failures are
+/// reported on stderr of the result rather than failing a run that already
+/// happened.
+pub fn run_o2_recorded<F>(
+  jobs: &JobTable,
+  history: &HistoryStore,
+  logs: &LogStore,
+  call: &O2Call,
+  mut on_line: F,
+) -> RunO2Result
+where
+  F: FnMut(&'static str, &str),
+{
+  let project = project_for_verb(&call.verb);
+  let source = project.as_deref().unwrap_or("o2");
+  let mut result = run_o2_job(jobs, call, |stream, line| {
+    logs.append(source, stream, line, Some(&call.job_id));
+    on_line(stream, line);
+  });
+
+  let info = jobs.get(&call.job_id);
+  let record = HistoryRecord {
+    id: call.job_id.clone(),
+    verb: call.verb.clone(),
+    project,
+    started_at_ms: info.as_ref().map(|j| j.started_at_ms).unwrap_or(0),
+    ended_at_ms: info.as_ref().and_then(|j| j.ended_at_ms).unwrap_or_else(now_ms),
+    status: info.map(|j| j.state).unwrap_or(JobState::Failed),
+    code: result.code,
+    stdout: result.stdout.clone(),
+    stderr: result.stderr.clone(),
+    args: call.args.clone(),
+    output_truncated: false,
+    trigger: call.trigger.to_string(),
+  };
+  if let Err(e) = history.append(record) {
+    result.stderr.push_str(&format!("run_o2: history not recorded: {e}\n"));
   }
+
+  result
 }
 
+// run_o2 is blocking; keep it off the main thread so o2_cancel stays reachable.
+// `args` are named arguments checked against the verb's declared params.
+// Destructive verbs also need `confirm_token` from o2_prepare_verb (and
+// `confirm_phrase` when the verb declares one).
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+pub fn run_o2(
+  jobs: State<'_, JobTable>,
+  history: State<'_, HistoryStore>,
+  logs: State<'_, LogStore>,
+  tokens: State<'_, ConfirmTokens>,
+  verb: String,
+  args: Option<Map<String, Value>>,
+  confirm_token: Option<String>,
+  confirm_phrase: Option<String>,
+) -> RunO2Result {
+  let checked = check_verb(&verb, args.as_ref()).and_then(|(info, args)| {
+    tokens.check(&info, &args, confirm_token.as_deref(), confirm_phrase.as_deref())?;
+    Ok(O2Call::new(info.verb, args, "ui"))
+  });
+  match checked {
+    Ok(call) => run_o2_recorded(&jobs, &history, &logs, &call, |_, _| {}),
+    Err(e) => RunO2Result::rejected(e),
+  }
+}
+
+/// Streaming variant of `run_o2`: returns a job id immediately, then emits
+/// `o2-output` per line and a single `o2-finished` when the child exits.
 #[tauri::command]
-pub fn run_o2(verb: String) -> RunO2Result {
-  // Defensive trim; keep it as one argument.
-  let v = verb.trim().to_string();
-  if v.is_empty() {
-    return RunO2Result {
-      ok: false,
-      code: 1,
-      stdout: "".to_string(),
-      stderr: "empty verb".to_string(),
+#[allow(clippy::too_many_arguments)]
+pub fn run_o2_stream(
+  app: AppHandle,
+  jobs: State<'_, JobTable>,
+  history: State<'_, HistoryStore>,
+  logs: State<'_, LogStore>,
+  tokens: State<'_, ConfirmTokens>,
+  verb: String,
+  args: Option<Map<String, Value>>,
+  confirm_token: Option<String>,
+  confirm_phrase: Option<String>,
+) -> Result<String, O2Error> {
+  let (info, args) = check_verb(&verb, args.as_ref())?;
+  tokens.check(&info, &args, confirm_token.as_deref(), confirm_phrase.as_deref())?;
+  let call = O2Call::new(info.verb, args, "ui");
+
+  let job_id = call.job_id.clone();
+  let jobs = jobs.inner().clone();
+  let history = history.inner().clone();
+  let logs = logs.inner().clone();
+  thread::spawn(move || {
+    let result = run_o2_recorded(&jobs, &history, &logs, &call, |stream, line| {
+      let _ = app.emit(
+        O2_OUTPUT_EVENT,
+        O2OutputEvent {
+          job_id: call.job_id.clone(),
+          stream,
+          line: line.to_string(),
+        },
+      );
+    });
+    let _ = app.emit(
+      O2_FINISHED_EVENT,
+      O2FinishedEvent {
+        job_id: call.job_id,
+        verb: call.verb,
+        result,
+      },
+    );
+  });
+
+  Ok(job_id)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn chatty_verb_still_times_out() {
+    let root = std::env::temp_dir().join(format!("radcontrol-o2-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("scripts")).unwrap();
+    std::fs::write(
+      root.join("scripts/run_o2.sh"),
+      "while :; do echo tick; sleep 0.01; done\n",
+    )
+    .unwrap();
+
+    let jobs = JobTable::default();
+    let call = O2Call {
+      timeout: Some(Duration::from_millis(300)),
+      root: Some(root.display().to_string()),
+      ..O2Call::new("chatty".to_string(), O2Args::new(), "test")
     };
+    let mut lines = 0;
+    let result = run_o2_job(&jobs, &call, |_, _| lines += 1);
+    let _ = std::fs::remove_dir_all(&root);
+
+    assert!(result.timed_out);
+    assert!(!result.ok);
+    assert!(lines > 0);
+    // SIGTERM is enough for bash; nothing should wait for the KILL window.
+    assert!(result.elapsed_ms < TERM_GRACE.as_millis() as u64);
+    assert_eq!(jobs.get(&call.job_id).map(|j| j.state), Some(JobState::TimedOut));
   }
-  run_o2_command(&v)
-}
\ No newline at end of file
+}
@@ -0,0 +1,512 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use super::history::{HistoryFilter, HistoryStore, HistorySummary};
+use super::jobs::{now_ms, JobInfo, JobState, JobTable};
+use super::ports::{dev_server_port, kill_port, port_owners, proc_info, PortOwners, ProcId, ProcInfo};
+use super::registry::load_registry;
+use super::supervisor::{ProcState, Supervisor};
+use crate::fsutil::write_atomic;
+use crate::git::git;
+use crate::procfs;
+
+// Under the app data dir: incident-<local time, ms>.json per real run.
+const INCIDENTS_DIR: &str = "incidents";
+const RECENT_JOBS: usize = 20;
+const PORT_STOP_TIMEOUT: Duration = Duration::from_secs(5);
+// A cancelled job gets SIGTERM, then SIGKILL after 3s; allow for the drain.
+const JOB_STOP_TIMEOUT: Duration = Duration::from_secs(5);
+const JOB_STOP_POLL: Duration = Duration::from_millis(100);
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetKind {
+    /// A running O2 job; cancelled first so it can't start anything new.
+    Job,
+    /// A process group owned by the supervisor.
+    Supervised,
+    /// Whatever else listens on a registered project port.
+    Port,
+}
+
+/// One thing panic_stop stops, in the order it stops them.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PanicTarget {
+    pub order: usize,
+    pub kind: TargetKind,
+    pub key: Option<String>,
+    pub port: Option<u16>,
+    pub job_id: Option<String>,
+    pub processes: Vec<ProcInfo>,
+    /// Set when the target is listed but deliberately left running.
+    pub skip_reason: Option<String>,
+    /// Set when the target couldn't be inspected; it isn't stopped and its
+    /// outcome records the error.
+    pub error: Option<String>,
+}
+
+/// Per-project state captured before anything is stopped.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectRecord {
+    pub key: String,
+    pub port: Option<u16>,
+    pub listening: bool,
+    pub repo: Option<String>,
+    pub git_branch: Option<String>,
+    pub git_head: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetOutcome {
+    pub order: usize,
+    pub ok: bool,
+    pub detail: String,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PanicReport {
+    pub dry_run: bool,
+    pub started_at_ms: u64,
+    pub ended_at_ms: u64,
+    pub targets: Vec<PanicTarget>,
+    pub projects: Vec<ProjectRecord>,
+    pub running_jobs: Vec<JobInfo>,
+    pub recent_jobs: Vec<HistorySummary>,
+    /// Why project ports weren't planned; jobs and supervised groups still were.
+    pub registry_error: Option<String>,
+    /// Empty for a dry run.
+    pub outcomes: Vec<TargetOutcome>,
+    /// The incident file; None for a dry run.
+    pub report_path: Option<String>,
+}
+
+fn group_members(pgid: u32) -> Vec<ProcInfo> {
+    let mut pids: Vec<u32> = procfs::all_pids()
+        .into_iter()
+        .filter(|pid| procfs::stat(*pid).is_some_and(|s| s.pgrp == pgid))
+        .collect();
+    pids.sort_unstable();
+    pids.into_iter().filter_map(proc_info).collect()
+}
+
+/// A registry project's port as seen at plan time.
+struct PortProbe {
+    key: String,
+    port: u16,
+    owners: Result<PortOwners, String>,
+}
+
+/// Number `jobs`, then `supervised`, then the port targets in that order.
+/// Ports whose owners all sit in one of `supervised_groups` (`pgrp_of`
+/// looks up a pid's group) are covered by that group and not listed again;
+/// a port that couldn't be inspected is listed with its error.
+fn assemble(
+    jobs: Vec<PanicTarget>,
+    supervised: Vec<PanicTarget>,
+    supervised_groups: &HashSet<u32>,
+    ports: Vec<PortProbe>,
+    pgrp_of: impl Fn(u32) -> Option<u32>,
+) -> Vec<PanicTarget> {
+    let mut targets: Vec<PanicTarget> = jobs.into_iter().chain(supervised).collect();
+    for probe in ports {
+        let target = |processes, skip_reason, error| PanicTarget {
+            order: 0,
+            kind: TargetKind::Port,
+            key: Some(probe.key.clone()),
+            port: Some(probe.port),
+            job_id: None,
+            processes,
+            skip_reason,
+            error,
+        };
+        match probe.owners {
+            // One port that can't be inspected shouldn't keep the rest running.
+            Err(e) => targets.push(target(Vec::new(), None, Some(e))),
+            Ok(owners) if owners.processes.is_empty() => {}
+            Ok(owners) => {
+                let covered = owners
+                    .processes
+                    .iter()
+                    .all(|pi| pgrp_of(pi.pid).is_some_and(|g| supervised_groups.contains(&g)));
+                if !covered {
+                    // RadControl never stops itself, panic or not.
+                    targets.push(target(owners.processes, owners.protected_reason, None));
+                }
+            }
+        }
+    }
+    for (i, t) in targets.iter_mut().enumerate() {
+        t.order = i + 1;
+    }
+    targets
+}
+
+/// Running jobs, then supervised process groups, then remaining project
+/// ports. Jobs and supervised groups don't need the registry; when it can't
+/// be loaded they are still planned and only the port phase is skipped,
+/// with the error returned alongside.
+fn plan(
+    jobs: &JobTable,
+    supervisor: &Supervisor,
+    dev_port: Option<u16>,
+) -> (Vec<PanicTarget>, Vec<ProjectRecord>, Option<String>) {
+    let job_targets = jobs
+        .list()
+        .into_iter()
+        .filter(|j| j.state == JobState::Running)
+        .map(|job| PanicTarget {
+            order: 0,
+            kind: TargetKind::Job,
+            key: None,
+            port: None,
+            processes: job.pid.map(group_members).unwrap_or_default(),
+            job_id: Some(job.id),
+            skip_reason: None,
+            error: None,
+        })
+        .collect();
+
+    let mut supervised_groups = HashSet::new();
+    let supervised = supervisor
+        .list()
+        .into_iter()
+        .filter(|s| s.state != ProcState::Exited)
+        .map(|sp| {
+            supervised_groups.insert(sp.pid);
+            PanicTarget {
+                order: 0,
+                kind: TargetKind::Supervised,
+                processes: group_members(sp.pid),
+                key: Some(sp.key),
+                port: sp.port,
+                job_id: None,
+                skip_reason: None,
+                error: None,
+            }
+        })
+        .collect();
+
+    let (projects, registry_error) = match load_registry() {
+        Ok(reg) => (reg.projects, None),
+        Err(e) => (Vec::new(), Some(e)),
+    };
+    let mut records = Vec::new();
+    let mut probes = Vec::new();
+    for p in &projects {
+        let owners = p.port.map(|port| port_owners(port, dev_port));
+        let repo = p.repo_dir().ok();
+        let rev = |args: &[&str]| repo.as_ref().and_then(|r| git(r, args)).map(|s| s.trim().to_string());
+        records.push(ProjectRecord {
+            key: p.key.clone(),
+            port: p.port,
+            listening: owners.as_ref().is_some_and(|o| o.as_ref().is_ok_and(|o| !o.processes.is_empty())),
+            git_branch: rev(&["rev-parse", "--abbrev-ref", "HEAD"]),
+            git_head: rev(&["rev-parse", "HEAD"]),
+            repo: repo.map(|r| r.display().to_string()),
+        });
+        if let (Some(port), Some(owners)) = (p.port, owners) {
+            probes.push(PortProbe {
+                key: p.key.clone(),
+                port,
+                owners,
+            });
+        }
+    }
+
+    let targets = assemble(job_targets, supervised, &supervised_groups, probes, |pid| {
+        procfs::stat(pid).map(|s| s.pgrp)
+    });
+    (targets, records, registry_error)
+}
+
+/// Cancel a job and wait, up to JOB_STOP_TIMEOUT, for it to end, so the
+/// next phase doesn't race whatever it was still starting.
+fn stop_job(jobs: &JobTable, id: &str) -> Result<String, String> {
+    if let Err(e) = jobs.cancel(id) {
+        // It ended (or was pruned) between planning and now: nothing to stop.
+        return match jobs.get(id).map(|j| j.state) {
+            Some(JobState::Running) => Err(e),
+            Some(state) => Ok(format!("job {id} already ended ({state:?})")),
+            None => Ok(format!("job {id} already ended")),
+        };
+    }
+    let deadline = Instant::now() + JOB_STOP_TIMEOUT;
+    loop {
+        match jobs.get(id).map(|j| j.state) {
+            Some(JobState::Running) if Instant::now() < deadline => thread::sleep(JOB_STOP_POLL),
+            Some(JobState::Running) => {
+                return Err(format!(
+                    "job {id} still running {}s after cancel",
+                    JOB_STOP_TIMEOUT.as_secs()
+                ))
+            }
+            Some(state) => return Ok(format!("cancelled job {id} ({state:?})")),
+            None => return Ok(format!("cancelled job {id}")),
+        }
+    }
+}
+
+fn stop_target(t: &PanicTarget, jobs: &JobTable, supervisor: &Supervisor, dev_port: Option<u16>) -> TargetOutcome {
+    if let Some(e) = &t.error {
+        return TargetOutcome {
+            order: t.order,
+            ok: false,
+            detail: e.clone(),
+        };
+    }
+    let res: Result<String, String> = match t.kind {
+        TargetKind::Job => stop_job(jobs, t.job_id.as_deref().unwrap_or_default()),
+        TargetKind::Supervised => {
+            let key = t.key.as_deref().unwrap_or_default();
+            supervisor.stop(key).map(|sp| format!("stopped {key} (pid {})", sp.pid))
+        }
+        TargetKind::Port => {
+            let port = t.port.unwrap_or_default();
+            // Only what the report recorded; a new owner is left alone.
+            let expected: Vec<ProcId> = t.processes.iter().map(ProcId::from).collect();
+            kill_port(port, &expected, dev_port, false, PORT_STOP_TIMEOUT).and_then(|r| {
+                let detail = format!(
+                    "port {port}: {} exited, {} killed, {} survived",
+                    r.exited.len(),
+                    r.killed.len(),
+                    r.survivors.len()
+                );
+                if r.survivors.is_empty() {
+                    Ok(detail)
+                } else {
+                    Err(detail)
+                }
+            })
+        }
+    };
+    TargetOutcome {
+        order: t.order,
+        ok: res.is_ok(),
+        detail: res.unwrap_or_else(|e| e),
+    }
+}
+
+fn incident_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("no app data dir: {e}"))?
+        .join(INCIDENTS_DIR);
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S%.3f").to_string();
+    Ok(unused_incident_path(&dir, &stamp))
+}
+
+/// `incident-<stamp>.json` in `dir`, with a counter added if two runs land
+/// on the same millisecond.
+fn unused_incident_path(dir: &Path, stamp: &str) -> PathBuf {
+    let mut path = dir.join(format!("incident-{stamp}.json"));
+    let mut n = 1;
+    while path.exists() {
+        path = dir.join(format!("incident-{stamp}-{n}.json"));
+        n += 1;
+    }
+    path
+}
+
+/// Stop every running O2 job, supervised process and project port, in that
+/// order, after recording what was running. With `dry_run` nothing is
+/// stopped or written; the report lists exactly what would be.
+#[tauri::command(async)]
+pub fn o2_panic_stop(
+    app: AppHandle,
+    jobs: State<'_, JobTable>,
+    history: State<'_, HistoryStore>,
+    supervisor: State<'_, Supervisor>,
+    dry_run: bool,
+) -> Result<PanicReport, String> {
+    let started_at_ms = now_ms();
+    let dev_port = dev_server_port(&app);
+    let (targets, projects, registry_error) = plan(&jobs, &supervisor, dev_port);
+
+    let mut report = PanicReport {
+        dry_run,
+        started_at_ms,
+        ended_at_ms: started_at_ms,
+        projects,
+        running_jobs: jobs.list().into_iter().filter(|j| j.state == JobState::Running).collect(),
+        recent_jobs: history
+            .list(&HistoryFilter {
+                limit: Some(RECENT_JOBS),
+                ..Default::default()
+            })
+            .unwrap_or_default(),
+        registry_error,
+        targets,
+        outcomes: Vec::new(),
+        report_path: None,
+    };
+    if dry_run {
+        return Ok(report);
+    }
+
+    // Written before stopping anything so the record exists even if a
+    // stop hangs or takes RadControl down with it.
+    let path = incident_path(&app)?;
+    let write = |r: &PanicReport| {
+        let json = serde_json::to_vec_pretty(r).map_err(|e| format!("Failed to serialize incident: {e}"))?;
+        write_atomic(&path, &json)
+    };
+    report.report_path = Some(path.display().to_string());
+    write(&report)?;
+
+    for t in report.targets.iter().filter(|t| t.skip_reason.is_none()) {
+        report.outcomes.push(stop_target(t, &jobs, &supervisor, dev_port));
+    }
+    report.ended_at_ms = now_ms();
+    write(&report)?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn proc(pid: u32) -> ProcInfo {
+        ProcInfo {
+            pid,
+            ppid: 1,
+            cmdline: None,
+            cwd: None,
+            started_at_ms: Some(1),
+        }
+    }
+
+    fn target(kind: TargetKind, key: &str) -> PanicTarget {
+        PanicTarget {
+            order: 0,
+            kind,
+            key: Some(key.to_string()),
+            port: None,
+            job_id: None,
+            processes: Vec::new(),
+            skip_reason: None,
+            error: None,
+        }
+    }
+
+    fn probe(key: &str, port: u16, pids: &[u32]) -> PortProbe {
+        PortProbe {
+            key: key.to_string(),
+            port,
+            owners: Ok(PortOwners {
+                port,
+                processes: pids.iter().copied().map(proc).collect(),
+                holders: pids.first().copied().into_iter().collect(),
+                protected_reason: None,
+            }),
+        }
+    }
+
+    // pids 100..200 sit in group 100 (supervised); everything else in its own.
+    fn pgrp(pid: u32) -> Option<u32> {
+        Some(if (100..200).contains(&pid) { 100 } else { pid })
+    }
+
+    #[test]
+    fn jobs_then_supervised_then_ports_numbered_in_order() {
+        let targets = assemble(
+            vec![target(TargetKind::Job, "j1"), target(TargetKind::Job, "j2")],
+            vec![target(TargetKind::Supervised, "web")],
+            &HashSet::from([100]),
+            vec![probe("api", 4000, &[300]), probe("docs", 5000, &[400])],
+            pgrp,
+        );
+        let got: Vec<(usize, TargetKind, &str)> = targets
+            .iter()
+            .map(|t| (t.order, t.kind, t.key.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            got,
+            [
+                (1, TargetKind::Job, "j1"),
+                (2, TargetKind::Job, "j2"),
+                (3, TargetKind::Supervised, "web"),
+                (4, TargetKind::Port, "api"),
+                (5, TargetKind::Port, "docs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn ports_covered_by_a_supervised_group_are_not_listed_again() {
+        let targets = assemble(
+            Vec::new(),
+            vec![target(TargetKind::Supervised, "web")],
+            &HashSet::from([100]),
+            vec![
+                // Entirely inside the supervised group.
+                probe("web", 3000, &[100, 101]),
+                // One owner outside it: still a target.
+                probe("mixed", 3001, &[150, 300]),
+                // Nothing listening.
+                probe("idle", 3002, &[]),
+            ],
+            pgrp,
+        );
+        let keys: Vec<&str> = targets.iter().map(|t| t.key.as_deref().unwrap()).collect();
+        assert_eq!(keys, ["web", "mixed"]);
+        assert_eq!(targets[1].kind, TargetKind::Port);
+        assert_eq!(targets[1].processes.len(), 2);
+    }
+
+    #[test]
+    fn uninspectable_ports_are_listed_with_their_error() {
+        let broken = PortProbe {
+            key: "api".to_string(),
+            port: 4000,
+            owners: Err("permission denied".to_string()),
+        };
+        let targets = assemble(Vec::new(), Vec::new(), &HashSet::new(), vec![broken, probe("docs", 5000, &[400])], pgrp);
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].error.as_deref(), Some("permission denied"));
+        assert!(targets[1].error.is_none());
+    }
+
+    #[test]
+    fn stopping_a_job_that_already_ended_succeeds() {
+        let jobs = JobTable::default();
+        jobs.register("done", "v");
+        jobs.finish("done", JobState::Succeeded);
+        assert!(stop_job(&jobs, "done").is_ok());
+        assert!(stop_job(&jobs, "pruned").is_ok());
+
+        jobs.register("running", "v");
+        let finisher = jobs.clone();
+        let t = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            finisher.finish("running", JobState::Cancelled);
+        });
+        assert!(stop_job(&jobs, "running").unwrap().contains("Cancelled"));
+        t.join().unwrap();
+    }
+
+    #[test]
+    fn incident_names_never_collide() {
+        let dir = std::env::temp_dir().join(format!("radcontrol-incidents-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let first = unused_incident_path(&dir, "20260101-000000.000");
+        fs::write(&first, "{}").unwrap();
+        let second = unused_incident_path(&dir, "20260101-000000.000");
+        fs::write(&second, "{}").unwrap();
+        let third = unused_incident_path(&dir, "20260101-000000.000");
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(first.ends_with("incident-20260101-000000.000.json"));
+        assert!(second.ends_with("incident-20260101-000000.000-1.json"));
+        assert!(third.ends_with("incident-20260101-000000.000-2.json"));
+    }
+}
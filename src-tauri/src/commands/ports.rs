@@ -0,0 +1,402 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use crate::{process, procfs};
+
+const DEFAULT_KILL_TIMEOUT_MS: u64 = 5000;
+const KILL_POLL: Duration = Duration::from_millis(100);
+// How long SIGKILLed processes get to disappear before we call them survivors.
+const SIGKILL_SETTLE: Duration = Duration::from_secs(1);
+
+/// Mirrors `PortStatus` in the UI.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PortStatus {
+    pub port: u16,
+    pub listening: bool,
+    pub pid: Option<u32>,
+    pub cmd: Option<String>,
+    pub err: Option<String>,
+}
+
+/// LISTEN state plus owning pid/cmdline for each port, from a single pass
+/// over /proc. The owner is left empty when it belongs to a process we can't
+/// inspect.
+pub fn port_status(ports: &[u16]) -> Vec<PortStatus> {
+    let listening = match procfs::listening_sockets() {
+        Ok(m) => m,
+        Err(e) => {
+            return ports
+                .iter()
+                .map(|&port| PortStatus {
+                    port,
+                    listening: false,
+                    pid: None,
+                    cmd: None,
+                    err: Some(e.clone()),
+                })
+                .collect();
+        }
+    };
+
+    let wanted: HashSet<u64> = ports
+        .iter()
+        .filter_map(|p| listening.get(p))
+        .flatten()
+        .copied()
+        .collect();
+    let owners = procfs::socket_owners(&wanted);
+
+    ports
+        .iter()
+        .map(|&port| {
+            let pid = listening
+                .get(&port)
+                .and_then(|inodes| inodes.iter().find_map(|i| owners.get(i).copied()));
+
+            PortStatus {
+                port,
+                listening: listening.contains_key(&port),
+                pid,
+                cmd: pid.and_then(procfs::cmdline),
+                err: None,
+            }
+        })
+        .collect()
+}
+
+// Walking /proc/*/fd takes a moment; keep it off the main thread.
+#[tauri::command(async)]
+pub fn o2_port_status(ports: Vec<u16>) -> Vec<PortStatus> {
+    let mut ports = ports;
+    ports.sort_unstable();
+    ports.dedup();
+    port_status(&ports)
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    pub cmdline: Option<String>,
+    pub cwd: Option<String>,
+    pub started_at_ms: Option<u64>,
+}
+
+/// A process as the user saw it in a preview. The start time tells a reused
+/// pid apart from the process that was confirmed.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcId {
+    pub pid: u32,
+    pub started_at_ms: Option<u64>,
+}
+
+impl From<&ProcInfo> for ProcId {
+    fn from(p: &ProcInfo) -> Self {
+        ProcId {
+            pid: p.pid,
+            started_at_ms: p.started_at_ms,
+        }
+    }
+}
+
+/// What o2_kill_port would terminate, for the confirmation dialog.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PortOwners {
+    pub port: u16,
+    /// Socket holders first, then their descendants.
+    pub processes: Vec<ProcInfo>,
+    /// Pids holding the listening socket itself.
+    pub holders: Vec<u32>,
+    /// Set when the tree includes RadControl itself or its dev server;
+    /// killing it then requires `force`.
+    pub protected_reason: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct KillReport {
+    pub port: u16,
+    pub targeted: Vec<u32>,
+    /// Exited after SIGTERM alone.
+    pub exited: Vec<u32>,
+    /// Needed SIGKILL and are gone now.
+    pub killed: Vec<u32>,
+    /// Still alive after SIGKILL (or not signalable, e.g. another user's).
+    pub survivors: Vec<u32>,
+}
+
+pub fn proc_info(pid: u32) -> Option<ProcInfo> {
+    let st = procfs::stat(pid)?;
+    Some(ProcInfo {
+        pid,
+        ppid: st.ppid,
+        cmdline: procfs::cmdline(pid),
+        cwd: procfs::cwd(pid),
+        started_at_ms: procfs::start_time_ms(st.starttime),
+    })
+}
+
+/// Port of RadControl's own dev server (`build.devUrl`), in dev builds only.
+pub fn dev_server_port(app: &AppHandle) -> Option<u16> {
+    if !tauri::is_dev() {
+        return None;
+    }
+    app.config().build.dev_url.as_ref()?.port_or_known_default()
+}
+
+/// Socket holders of `port` and everything they spawned. `dev_port` is
+/// RadControl's own dev server (see `dev_server_port`).
+pub fn port_owners(port: u16, dev_port: Option<u16>) -> Result<PortOwners, String> {
+    let listening = procfs::listening_sockets()?;
+    let inodes: HashSet<u64> = listening.get(&port).into_iter().flatten().copied().collect();
+
+    let mut roots: Vec<u32> = procfs::socket_holders(&inodes).into_values().flatten().collect();
+    roots.sort_unstable();
+    roots.dedup();
+
+    let tree = procfs::process_tree(&roots, &procfs::children_map());
+    let processes: Vec<ProcInfo> = tree.into_iter().filter_map(proc_info).collect();
+
+    let own = procfs::self_and_ancestors();
+    let protected_reason = if processes.iter().any(|p| own.contains(&p.pid)) {
+        Some("process tree includes RadControl itself".to_string())
+    } else if dev_port == Some(port) {
+        Some(format!("port {port} is RadControl's own dev server"))
+    } else {
+        None
+    };
+
+    Ok(PortOwners {
+        port,
+        processes,
+        holders: roots,
+        protected_reason,
+    })
+}
+
+fn wait_gone(pids: &[u32], deadline: Instant) -> Vec<u32> {
+    loop {
+        let alive: Vec<u32> = pids.iter().copied().filter(|p| procfs::is_alive(*p)).collect();
+        if alive.is_empty() || Instant::now() >= deadline {
+            return alive;
+        }
+        thread::sleep(KILL_POLL);
+    }
+}
+
+/// Pids of `expected` to signal: only those that still hold or descend from
+/// a holder of the port (`owners`, same pid and start time). Refuses when
+/// the port is held by something that wasn't previewed, when a previewed pid
+/// is still alive but no longer an owner or was reused (`alive` looks a pid
+/// up now), or when a target is RadControl itself (`own`) or the port is its
+/// dev server, unless `force`. Previewed processes that have exited are left
+/// out.
+fn select_targets(
+    owners: &PortOwners,
+    expected: &[ProcId],
+    alive: impl Fn(u32) -> Option<ProcId>,
+    own: &HashSet<u32>,
+    dev_port: Option<u16>,
+    force: bool,
+) -> Result<Vec<u32>, String> {
+    let port = owners.port;
+    let current: Vec<ProcId> = owners.processes.iter().map(ProcId::from).collect();
+    let unconfirmed: Vec<String> = owners
+        .holders
+        .iter()
+        .filter(|pid| !current.iter().any(|c| c.pid == **pid && expected.contains(c)))
+        .map(u32::to_string)
+        .collect();
+    if !unconfirmed.is_empty() {
+        return Err(format!(
+            "refusing to kill port {port}: it is now held by pid {} which wasn't in the preview",
+            unconfirmed.join(", ")
+        ));
+    }
+
+    let mut targeted = Vec::new();
+    for want in expected {
+        if current.contains(want) {
+            targeted.push(want.pid);
+            continue;
+        }
+        match alive(want.pid) {
+            Some(now) if now.started_at_ms != want.started_at_ms => {
+                return Err(format!(
+                    "refusing to kill port {port}: pid {} is a different process than the one previewed",
+                    want.pid
+                ));
+            }
+            Some(_) => {
+                return Err(format!(
+                    "refusing to kill port {port}: pid {} no longer holds it",
+                    want.pid
+                ));
+            }
+            None => {}
+        }
+    }
+
+    if !force {
+        if let Some(pid) = targeted.iter().find(|p| own.contains(p)) {
+            return Err(format!(
+                "refusing to kill port {port}: pid {pid} is RadControl itself (pass force to override)"
+            ));
+        }
+        if dev_port == Some(port) && !targeted.is_empty() {
+            return Err(format!(
+                "refusing to kill port {port}: it is RadControl's own dev server (pass force to override)"
+            ));
+        }
+    }
+    Ok(targeted)
+}
+
+/// SIGTERM the processes the user confirmed in a preview (`expected`) that
+/// still own the port, wait up to `timeout`, then SIGKILL what is left. See
+/// `select_targets` for when it refuses; the user then has to preview again.
+pub fn kill_port(
+    port: u16,
+    expected: &[ProcId],
+    dev_port: Option<u16>,
+    force: bool,
+    timeout: Duration,
+) -> Result<KillReport, String> {
+    let owners = port_owners(port, dev_port)?;
+    let alive = |pid| proc_info(pid).as_ref().map(ProcId::from);
+    let targeted = select_targets(
+        &owners,
+        expected,
+        alive,
+        &procfs::self_and_ancestors(),
+        dev_port,
+        force,
+    )?;
+
+    for pid in &targeted {
+        process::signal_pid(*pid, process::SIGTERM);
+    }
+    let after_term = wait_gone(&targeted, Instant::now() + timeout);
+
+    for pid in &after_term {
+        process::signal_pid(*pid, process::SIGKILL);
+    }
+    let survivors = wait_gone(&after_term, Instant::now() + SIGKILL_SETTLE);
+
+    Ok(KillReport {
+        port,
+        exited: targeted.iter().copied().filter(|p| !after_term.contains(p)).collect(),
+        killed: after_term.iter().copied().filter(|p| !survivors.contains(p)).collect(),
+        survivors,
+        targeted,
+    })
+}
+
+#[tauri::command(async)]
+pub fn o2_port_owners(app: AppHandle, port: u16) -> Result<PortOwners, String> {
+    port_owners(port, dev_server_port(&app))
+}
+
+/// Stop the processes listed in `expected`, taken from o2_port_owners.
+#[tauri::command(async)]
+pub fn o2_kill_port(
+    app: AppHandle,
+    port: u16,
+    expected: Vec<ProcId>,
+    force: Option<bool>,
+    timeout_ms: Option<u64>,
+) -> Result<KillReport, String> {
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_KILL_TIMEOUT_MS));
+    kill_port(port, &expected, dev_server_port(&app), force.unwrap_or(false), timeout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(pid: u32, started_at_ms: u64) -> ProcId {
+        ProcId {
+            pid,
+            started_at_ms: Some(started_at_ms),
+        }
+    }
+
+    fn owners(port: u16, holders: &[u32], procs: &[ProcId]) -> PortOwners {
+        PortOwners {
+            port,
+            processes: procs
+                .iter()
+                .map(|p| ProcInfo {
+                    pid: p.pid,
+                    ppid: 1,
+                    cmdline: None,
+                    cwd: None,
+                    started_at_ms: p.started_at_ms,
+                })
+                .collect(),
+            holders: holders.to_vec(),
+            protected_reason: None,
+        }
+    }
+
+    // Every pid in `live` is alive with that start time.
+    fn lookup(live: &[ProcId]) -> impl Fn(u32) -> Option<ProcId> + '_ {
+        move |pid| live.iter().copied().find(|p| p.pid == pid)
+    }
+
+    #[test]
+    fn targets_only_previewed_owners() {
+        let o = owners(3000, &[10], &[id(10, 1), id(11, 2)]);
+        let live = [id(10, 1), id(11, 2)];
+        let got = select_targets(&o, &[id(10, 1), id(11, 2)], lookup(&live), &HashSet::new(), None, false);
+        assert_eq!(got.unwrap(), [10, 11]);
+    }
+
+    #[test]
+    fn refuses_a_live_pid_that_is_not_an_owner() {
+        let o = owners(3000, &[10], &[id(10, 1)]);
+        let live = [id(10, 1), id(99, 5)];
+        let err = select_targets(&o, &[id(10, 1), id(99, 5)], lookup(&live), &HashSet::new(), None, false);
+        assert!(err.unwrap_err().contains("pid 99 no longer holds it"));
+    }
+
+    #[test]
+    fn unheld_port_kills_nothing() {
+        let o = owners(3000, &[], &[]);
+        let none = HashSet::new();
+        assert!(select_targets(&o, &[], lookup(&[]), &none, None, false).unwrap().is_empty());
+        // Previewed processes that exited since are simply gone.
+        assert!(select_targets(&o, &[id(10, 1)], lookup(&[]), &none, None, false).unwrap().is_empty());
+        // RadControl's own pid, passed in as if previewed, is never signalled.
+        let me = [id(std::process::id(), 7)];
+        let own = HashSet::from([std::process::id()]);
+        assert!(select_targets(&o, &me, lookup(&me), &own, None, true).is_err());
+    }
+
+    #[test]
+    fn refuses_reused_and_unpreviewed_pids() {
+        let o = owners(3000, &[10], &[id(10, 2)]);
+        let err = select_targets(&o, &[id(10, 1)], lookup(&[id(10, 2)]), &HashSet::new(), None, false);
+        assert!(err.unwrap_err().contains("wasn't in the preview"));
+
+        let o = owners(3000, &[], &[]);
+        let err = select_targets(&o, &[id(10, 1)], lookup(&[id(10, 2)]), &HashSet::new(), None, false);
+        assert!(err.unwrap_err().contains("different process"));
+    }
+
+    #[test]
+    fn protects_radcontrol_and_its_dev_server_unless_forced() {
+        let o = owners(1420, &[10], &[id(10, 1)]);
+        let live = [id(10, 1)];
+        let own = HashSet::from([10]);
+        assert!(select_targets(&o, &[id(10, 1)], lookup(&live), &own, None, false).is_err());
+        assert!(select_targets(&o, &[id(10, 1)], lookup(&live), &HashSet::new(), Some(1420), false).is_err());
+        assert_eq!(select_targets(&o, &[id(10, 1)], lookup(&live), &own, Some(1420), true).unwrap(), [10]);
+    }
+}
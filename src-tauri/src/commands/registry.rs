@@ -1,5 +1,213 @@
-use serde_json::Value;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::verbs::check_verb_name;
+use crate::fsutil::write_atomic;
+
+// Backups of projects.json kept under registry/backups/; oldest pruned first.
+const KEEP_BACKUPS: usize = 20;
+
+// Serialized names of the fields `Project` models. On update, these follow
+// the incoming project; anything else on the existing row is preserved.
+const MODELED_FIELDS: &[&str] = &[
+    "key",
+    "label",
+    "repoHint",
+    "port",
+    "url",
+    "o2StartKey",
+    "o2SnapshotKey",
+    "o2CommitKey",
+    "o2MapKey",
+    "o2ProofPackKey",
+    "org",
+    "kind",
+    "repoPath",
+    "healthExpect",
+    "start",
+];
+
+// Serializes read-modify-write cycles on projects.json.
+static REGISTRY_WRITE: Mutex<()> = Mutex::new(());
+
+/// How the supervisor launches a project's dev server. `command` is an argv,
+/// not a shell line; use `["bash", "-lc", "..."]` when a login shell is needed.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StartSpec {
+    pub command: Vec<String>,
+    /// Relative to the project's checkout; defaults to the checkout itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+/// One row of `registry/projects.json`. Mirrors `ProjectRow` in the UI; any
+/// field we don't model is kept in `extra` so it round-trips untouched.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub key: String,
+    #[serde(default)]
+    pub label: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_hint: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub o2_start_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub o2_snapshot_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub o2_commit_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub o2_map_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub o2_proof_pack_key: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_path: Option<String>,
+
+    /// Substring the health check expects in the response body at `url`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_expect: Option<String>,
+
+    /// Lets the supervisor own the dev server instead of `o2StartKey`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<StartSpec>,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Project {
+    /// Local checkout for this row: `repoPath`, else `repoHint`, with a
+    /// leading `~` or `$HOME` expanded. Errors when neither is set.
+    pub fn repo_dir(&self) -> Result<PathBuf, String> {
+        let raw = self
+            .repo_path
+            .as_deref()
+            .or(self.repo_hint.as_deref())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| format!("project {} has no repoPath or repoHint", self.key))?;
+
+        let rest = raw
+            .strip_prefix("~")
+            .or_else(|| raw.strip_prefix("$HOME"))
+            .filter(|r| r.is_empty() || r.starts_with('/'));
+        match rest {
+            Some(rest) => {
+                let home = std::env::var("HOME").map_err(|e| format!("HOME not set: {e}"))?;
+                Ok(PathBuf::from(format!("{home}{rest}")))
+            }
+            None => Ok(PathBuf::from(raw)),
+        }
+    }
+
+    /// Field-level checks that serde can't express.
+    fn validate(&mut self) -> Result<(), String> {
+        self.key = self.key.trim().to_string();
+        if self.key.is_empty() {
+            return Err("key is empty".to_string());
+        }
+
+        if self.label.trim().is_empty() {
+            self.label = self.key.clone();
+        }
+
+        if self.port == Some(0) {
+            return Err("port must be between 1 and 65535".to_string());
+        }
+
+        if let Some(url) = &self.url {
+            check_url(url)?;
+        }
+
+        if let Some(start) = &self.start {
+            if start.command.first().is_none_or(|c| c.trim().is_empty()) {
+                return Err("start.command must name a program".to_string());
+            }
+            if let Some(bad) = start.env.keys().find(|k| k.is_empty() || k.contains(['=', '\0'])) {
+                return Err(format!("start.env has an invalid name: {bad:?}"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Working directory for `start`: `start.cwd` resolved against the checkout.
+    pub fn start_dir(&self) -> Result<PathBuf, String> {
+        let repo = self.repo_dir()?;
+        match self.start.as_ref().and_then(|s| s.cwd.as_deref()) {
+            Some(cwd) => Ok(repo.join(cwd)),
+            None => Ok(repo),
+        }
+    }
+}
+
+/// Accepts `http(s)://host[:port][/path]`; enough to catch typos without
+/// pulling in a URL parser.
+fn check_url(url: &str) -> Result<(), String> {
+    let rest = url
+        .strip_prefix("http://")
+        .or_else(|| url.strip_prefix("https://"))
+        .ok_or_else(|| format!("url must start with http:// or https:// (got {url})"))?;
+
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let (host, port) = if let Some(end) = authority.strip_prefix('[').and_then(|a| a.find(']')) {
+        // [v6]:port
+        let (h, rest) = authority.split_at(end + 2);
+        (h, rest.strip_prefix(':'))
+    } else {
+        match authority.rsplit_once(':') {
+            Some((h, p)) => (h, Some(p)),
+            None => (authority, None),
+        }
+    };
+
+    if host.is_empty() || host.chars().any(char::is_whitespace) {
+        return Err(format!("url has no valid host (got {url})"));
+    }
+    if let Some(p) = port {
+        if p.parse::<u16>().map_or(true, |n| n == 0) {
+            return Err(format!("url has an invalid port (got {url})"));
+        }
+    }
+
+    Ok(())
+}
+
+/// A registry row that was skipped, reported next to the rows that loaded.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RowError {
+    pub index: usize,
+    pub key: Option<String>,
+    pub message: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryLoad {
+    pub path: String,
+    pub projects: Vec<Project>,
+    pub errors: Vec<RowError>,
+}
 
 fn read_json_array(path: &str) -> Result<Vec<Value>, String> {
     let s = fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
@@ -11,7 +219,7 @@ fn read_json_array(path: &str) -> Result<Vec<Value>, String> {
     }
 }
 
-fn o2_root() -> Result<String, String> {
+pub(crate) fn o2_root() -> Result<String, String> {
     // Match src-tauri/src/commands/o2.rs behavior:
     // Prefer explicit env if set; otherwise default to $HOME/dev/o2.
     if let Ok(p) = std::env::var("O2_ROOT") {
@@ -22,15 +230,608 @@ fn o2_root() -> Result<String, String> {
     Ok(format!("{home}/dev/o2"))
 }
 
-#[tauri::command]
-pub fn o2_list_projects() -> Result<String, String> {
+pub(crate) fn registry_path() -> Result<String, String> {
     let root = o2_root()?;
-    let registry_path = format!("{root}/registry/projects.json");
+    Ok(format!("{root}/registry/projects.json"))
+}
+
+/// Raw registry rows; errors when the registry file is missing.
+pub(crate) fn read_registry_rows() -> Result<Vec<Value>, String> {
+    let registry_path = registry_path()?;
 
     if !std::path::Path::new(&registry_path).is_file() {
         return Err(format!("O2 registry missing: {registry_path}"));
     }
 
-    let arr = read_json_array(&registry_path)?;
-    serde_json::to_string(&arr).map_err(|e| format!("Failed to serialize registry array: {e}"))
+    read_json_array(&registry_path)
+}
+
+/// Parse each row on its own so one bad entry doesn't hide the rest.
+fn parse_rows(path: String, rows: Vec<Value>) -> RegistryLoad {
+    let mut projects = Vec::new();
+    let mut errors = Vec::new();
+    let mut keys = HashSet::new();
+
+    for (index, row) in rows.into_iter().enumerate() {
+        let key = row.get("key").and_then(Value::as_str).map(str::to_string);
+
+        let parsed = serde_json::from_value::<Project>(row)
+            .map_err(|e| e.to_string())
+            .and_then(|mut p| p.validate().map(|_| p));
+
+        match parsed {
+            Ok(p) if !keys.insert(p.key.clone()) => errors.push(RowError {
+                index,
+                key,
+                message: format!("duplicate key: {}", p.key),
+            }),
+            Ok(p) => projects.push(p),
+            Err(message) => errors.push(RowError { index, key, message }),
+        }
+    }
+
+    RegistryLoad {
+        path,
+        projects,
+        errors,
+    }
+}
+
+/// Typed registry load shared by o2_list_projects and anything else that
+/// needs the project list.
+pub fn load_registry() -> Result<RegistryLoad, String> {
+    let path = registry_path()?;
+    let rows = read_registry_rows()?;
+    Ok(parse_rows(path, rows))
+}
+
+#[tauri::command]
+pub fn o2_list_projects() -> Result<RegistryLoad, String> {
+    load_registry()
+}
+
+fn row_key(row: &Value) -> Option<&str> {
+    row.get("key").and_then(Value::as_str).map(str::trim)
+}
+
+/// Validate `project` against the other rows. `replacing` names the row being
+/// edited, which is excluded from the uniqueness checks.
+fn validate_for_write(rows: &[Value], project: &mut Project, replacing: Option<&str>) -> Result<(), String> {
+    project.validate()?;
+
+    // The verb catalog would skip a hook it can't run; don't write one.
+    let hooks = [
+        ("o2StartKey", &project.o2_start_key),
+        ("o2SnapshotKey", &project.o2_snapshot_key),
+        ("o2CommitKey", &project.o2_commit_key),
+        ("o2MapKey", &project.o2_map_key),
+        ("o2ProofPackKey", &project.o2_proof_pack_key),
+    ];
+    for (field, verb) in hooks {
+        // An empty hook is an unset one.
+        if let Some(verb) = verb.as_deref().filter(|v| !v.trim().is_empty()) {
+            check_verb_name(verb).map_err(|e| format!("{field}: {e}"))?;
+        }
+    }
+
+    for row in rows {
+        let key = row_key(row);
+        if replacing.is_some() && key == replacing {
+            continue;
+        }
+
+        if key == Some(project.key.as_str()) {
+            return Err(format!("key already exists: {}", project.key));
+        }
+
+        if let Some(port) = project.port {
+            if row.get("port").and_then(Value::as_u64) == Some(port as u64) {
+                return Err(format!(
+                    "port {port} already used by {}",
+                    key.unwrap_or("(unnamed row)")
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Rebuild an edited row: keep the existing row's field order and any fields
+/// we don't model; modeled fields take the incoming value (or are dropped).
+fn merge_row(old: &Value, project: &Project) -> Result<Value, String> {
+    let Value::Object(new) = serde_json::to_value(project).map_err(|e| format!("Failed to serialize project: {e}"))? else {
+        return Err("Project did not serialize to an object".to_string());
+    };
+    let Some(old) = old.as_object() else {
+        return Ok(Value::Object(new));
+    };
+
+    let mut out = Map::new();
+    for (k, v) in old {
+        if let Some(nv) = new.get(k) {
+            out.insert(k.clone(), nv.clone());
+        } else if !MODELED_FIELDS.contains(&k.as_str()) {
+            out.insert(k.clone(), v.clone());
+        }
+    }
+    for (k, v) in new {
+        if !out.contains_key(&k) {
+            out.insert(k, v);
+        }
+    }
+
+    Ok(Value::Object(out))
+}
+
+fn backup_registry(path: &Path) -> Result<(), String> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| format!("No parent directory for {}", path.display()))?
+        .join("backups");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ");
+    let dest = dir.join(format!("projects.{stamp}.json"));
+    fs::copy(path, &dest).map_err(|e| format!("Failed to back up registry to {}: {e}", dest.display()))?;
+
+    let mut backups: Vec<_> = fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to list {}: {e}", dir.display()))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("projects.") && n.ends_with(".json"))
+        })
+        .collect();
+    backups.sort();
+
+    let excess = backups.len().saturating_sub(KEEP_BACKUPS);
+    for old in backups.into_iter().take(excess) {
+        let _ = fs::remove_file(old);
+    }
+
+    Ok(())
+}
+
+/// The one row a write-back command changes.
+enum RowEdit {
+    Append(Value),
+    Replace(usize, Value),
+    Remove(usize),
+}
+
+/// How the rows of projects.json are laid out, so an edited row looks like
+/// its neighbours.
+struct Layout {
+    /// Whitespace before each row on its line; None when rows share lines.
+    row_indent: Option<String>,
+    /// One level of indentation inside a row.
+    unit: String,
+}
+
+/// Where the top-level array and its elements sit in the file text.
+struct RowSpans {
+    /// Byte range of each element, without surrounding whitespace.
+    rows: Vec<(usize, usize)>,
+    /// Offsets of the array's `[` and `]`.
+    open: usize,
+    close: usize,
+}
+
+/// `text` must already parse as a JSON array.
+fn row_spans(text: &str) -> Result<RowSpans, String> {
+    let bytes = text.as_bytes();
+    let skip_ws = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        i
+    };
+
+    let open = skip_ws(0);
+    if bytes.get(open) != Some(&b'[') {
+        return Err("registry is not a JSON array".to_string());
+    }
+    let mut rows = Vec::new();
+    let mut i = open + 1;
+    loop {
+        i = skip_ws(i);
+        match bytes.get(i) {
+            Some(b']') => return Ok(RowSpans { rows, open, close: i }),
+            Some(_) => {}
+            None => return Err("registry array is not closed".to_string()),
+        }
+
+        let start = i;
+        let (mut depth, mut in_str, mut escaped) = (0usize, false, false);
+        while i < bytes.len() {
+            let b = bytes[i];
+            if in_str {
+                match b {
+                    _ if escaped => escaped = false,
+                    b'\\' => escaped = true,
+                    b'"' => in_str = false,
+                    _ => {}
+                }
+            } else {
+                match b {
+                    b'"' => in_str = true,
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' if depth > 0 => depth -= 1,
+                    b',' | b']' if depth == 0 => break,
+                    _ => {}
+                }
+            }
+            i += 1;
+        }
+        rows.push((start, start + text[start..i].trim_end().len()));
+        if bytes.get(i) == Some(&b',') {
+            i += 1;
+        }
+    }
+}
+
+fn detect_layout(text: &str, spans: &[(usize, usize)]) -> Layout {
+    let mut layout = Layout {
+        row_indent: Some("  ".to_string()),
+        unit: "  ".to_string(),
+    };
+    let Some(&(start, end)) = spans.first() else {
+        return layout;
+    };
+
+    let line_start = text[..start].rfind('\n').map_or(0, |n| n + 1);
+    let before = &text[line_start..start];
+    layout.row_indent = (text[..start].contains('\n') && before.trim().is_empty()).then(|| before.to_string());
+
+    // First indented line inside the row, relative to the row itself.
+    let base = layout.row_indent.as_deref().unwrap_or("").len();
+    if let Some(line) = text[start..end].lines().nth(1) {
+        let indent = &line[..line.len() - line.trim_start().len()];
+        if indent.len() > base {
+            layout.unit = indent[base..].to_string();
+        }
+    }
+    layout
+}
+
+/// `row` the way the file writes rows: compact when `compact`, otherwise
+/// pretty-printed with the file's indentation.
+fn render_row(row: &Value, layout: &Layout, compact: bool) -> Result<String, String> {
+    let fail = |e: serde_json::Error| format!("Failed to serialize project: {e}");
+    if compact {
+        return serde_json::to_string(row).map_err(fail);
+    }
+
+    let mut buf = Vec::new();
+    let fmt = serde_json::ser::PrettyFormatter::with_indent(layout.unit.as_bytes());
+    let mut ser = serde_json::Serializer::with_formatter(&mut buf, fmt);
+    row.serialize(&mut ser).map_err(fail)?;
+    let pretty = String::from_utf8(buf).map_err(|e| format!("Failed to serialize project: {e}"))?;
+
+    let indent = layout.row_indent.as_deref().unwrap_or("");
+    Ok(pretty.replace('\n', &format!("\n{indent}")))
+}
+
+/// Apply `edit` to the text of projects.json, leaving every other row and
+/// all surrounding whitespace exactly as they were.
+fn splice_row(text: &str, edit: &RowEdit) -> Result<String, String> {
+    let RowSpans { rows: spans, open, close } = row_spans(text)?;
+    let layout = detect_layout(text, &spans);
+    // New rows copy the first row's style; replaced rows keep their own.
+    let multiline = |(s, e): (usize, usize)| text[s..e].contains('\n');
+    let first_multiline = spans.first().is_none_or(|&sp| multiline(sp));
+
+    let mut out = String::with_capacity(text.len() + 256);
+    match *edit {
+        RowEdit::Replace(i, ref row) => {
+            let (s, e) = *spans.get(i).ok_or_else(|| format!("no registry row {i}"))?;
+            out.push_str(&text[..s]);
+            out.push_str(&render_row(row, &layout, !multiline((s, e)))?);
+            out.push_str(&text[e..]);
+        }
+        RowEdit::Remove(i) => {
+            let (s, e) = *spans.get(i).ok_or_else(|| format!("no registry row {i}"))?;
+            // Take the separator that follows the row, or for the last row
+            // the one before it, so the commas stay balanced.
+            let (cut_s, cut_e) = match (spans.get(i + 1), i.checked_sub(1).map(|p| spans[p])) {
+                (Some(&(next, _)), _) => (s, next),
+                (None, Some((_, prev_end))) => (prev_end, e),
+                (None, None) => (open + 1, close),
+            };
+            out.push_str(&text[..cut_s]);
+            out.push_str(&text[cut_e..]);
+        }
+        RowEdit::Append(ref row) => {
+            let rendered = render_row(row, &layout, !first_multiline)?;
+            match spans.last() {
+                Some(&(_, last_end)) => {
+                    out.push_str(&text[..last_end]);
+                    match &layout.row_indent {
+                        Some(indent) => out.push_str(&format!(",\n{indent}")),
+                        None => out.push_str(", "),
+                    }
+                    out.push_str(&rendered);
+                    out.push_str(&text[last_end..]);
+                }
+                None => {
+                    out.push_str(&text[..=open]);
+                    out.push_str(&format!("\n  {rendered}\n"));
+                    out.push_str(&text[close..]);
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Load the raw rows, let `edit` pick the one row to change, then back up
+/// and atomically replace projects.json. Only that row's text changes; the
+/// rest of the file keeps its formatting. Returns the reloaded registry.
+fn modify_registry<F>(edit: F) -> Result<RegistryLoad, String>
+where
+    F: FnOnce(&[Value]) -> Result<RowEdit, String>,
+{
+    let _guard = REGISTRY_WRITE.lock().unwrap_or_else(|e| e.into_inner());
+
+    let path = registry_path()?;
+    let rows = read_registry_rows()?;
+    let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    let edit = edit(&rows)?;
+    let out = splice_row(&text, &edit)?;
+
+    // The splice works on text; make sure it produced exactly the intended
+    // rows before anything is written.
+    let mut expected = rows;
+    match edit {
+        RowEdit::Append(row) => expected.push(row),
+        RowEdit::Replace(i, row) => expected[i] = row,
+        RowEdit::Remove(i) => {
+            expected.remove(i);
+        }
+    }
+    if serde_json::from_str::<Value>(&out).ok() != Some(Value::Array(expected)) {
+        return Err(format!("Refusing to write {path}: edit did not round-trip"));
+    }
+
+    backup_registry(Path::new(&path))?;
+    write_atomic(Path::new(&path), out.as_bytes())?;
+
+    load_registry()
+}
+
+#[tauri::command]
+pub fn o2_add_project(project: Project) -> Result<RegistryLoad, String> {
+    let mut project = project;
+    modify_registry(|rows| {
+        validate_for_write(rows, &mut project, None)?;
+        let row = serde_json::to_value(&project).map_err(|e| format!("Failed to serialize project: {e}"))?;
+        Ok(RowEdit::Append(row))
+    })
+}
+
+#[tauri::command]
+pub fn o2_update_project(key: String, project: Project) -> Result<RegistryLoad, String> {
+    let key = key.trim().to_string();
+    let mut project = project;
+    modify_registry(|rows| {
+        let idx = rows
+            .iter()
+            .position(|r| row_key(r) == Some(key.as_str()))
+            .ok_or_else(|| format!("no project with key: {key}"))?;
+
+        validate_for_write(rows, &mut project, Some(&key))?;
+        Ok(RowEdit::Replace(idx, merge_row(&rows[idx], &project)?))
+    })
+}
+
+#[tauri::command]
+pub fn o2_remove_project(key: String) -> Result<RegistryLoad, String> {
+    let key = key.trim().to_string();
+    modify_registry(|rows| {
+        rows.iter()
+            .position(|r| row_key(r) == Some(key.as_str()))
+            .map(RowEdit::Remove)
+            .ok_or_else(|| format!("no project with key: {key}"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(rows: Value) -> RegistryLoad {
+        let Value::Array(rows) = rows else {
+            panic!("rows must be an array");
+        };
+        parse_rows("projects.json".to_string(), rows)
+    }
+
+    #[test]
+    fn duplicate_key_keeps_the_first_row() {
+        let load = parse(json!([
+            { "key": "tbis", "port": 3000 },
+            { "key": " tbis ", "port": 3001 },
+            { "key": "dqotd" },
+        ]));
+        let keys: Vec<&str> = load.projects.iter().map(|p| p.key.as_str()).collect();
+        assert_eq!(keys, ["tbis", "dqotd"]);
+        assert_eq!(load.errors.len(), 1);
+        assert_eq!(load.errors[0].index, 1);
+        assert_eq!(load.errors[0].message, "duplicate key: tbis");
+    }
+
+    #[test]
+    fn bad_ports_skip_only_their_row() {
+        let load = parse(json!([
+            { "key": "zero", "port": 0 },
+            { "key": "big", "port": 70000 },
+            { "key": "text", "port": "3000" },
+            { "key": "ok", "port": 65535 },
+        ]));
+        assert_eq!(load.projects.len(), 1);
+        assert_eq!(load.projects[0].port, Some(65535));
+        let bad: Vec<_> = load.errors.iter().map(|e| e.key.as_deref().unwrap()).collect();
+        assert_eq!(bad, ["zero", "big", "text"]);
+    }
+
+    #[test]
+    fn empty_key_is_rejected_and_label_defaults() {
+        let load = parse(json!([{ "key": "  " }, { "key": "tbis", "label": " " }]));
+        assert_eq!(load.errors[0].message, "key is empty");
+        assert_eq!(load.projects[0].label, "tbis");
+    }
+
+    #[test]
+    fn check_url_accepts_http_and_https() {
+        for url in [
+            "http://localhost:3000",
+            "https://example.com/health?x=1",
+            "http://[::1]:1420/",
+            "http://127.0.0.1",
+        ] {
+            assert_eq!(check_url(url), Ok(()), "{url}");
+        }
+    }
+
+    #[test]
+    fn check_url_rejects_typos() {
+        for url in [
+            "localhost:3000",
+            "ftp://example.com",
+            "http://",
+            "http://:3000",
+            "http://local host",
+            "http://localhost:0",
+            "http://localhost:99999",
+            "http://localhost:abc/",
+            "http://[::1]:x",
+        ] {
+            assert!(check_url(url).is_err(), "{url}");
+        }
+
+        let load = parse(json!([{ "key": "tbis", "url": "localhost:3000" }]));
+        assert!(load.projects.is_empty());
+        assert!(load.errors[0].message.starts_with("url must start with"));
+    }
+
+    #[test]
+    fn unknown_fields_round_trip() {
+        let row = json!({
+            "key": "tbis",
+            "port": 3000,
+            "notes": "keep me",
+            "owner": { "team": "web", "oncall": ["a", "b"] },
+        });
+        let load = parse(json!([row.clone()]));
+        let p = &load.projects[0];
+        assert_eq!(p.extra.get("notes"), Some(&json!("keep me")));
+
+        let mut back = serde_json::to_value(p).unwrap();
+        // validate() fills in the label; everything else comes back as read.
+        back.as_object_mut().unwrap().remove("label");
+        assert_eq!(back, row);
+
+        let merged = merge_row(&row, p).unwrap();
+        assert_eq!(merged["owner"], row["owner"]);
+    }
+
+    #[test]
+    fn hook_verbs_must_be_single_words() {
+        let rows = vec![json!({ "key": "tbis", "port": 3000 })];
+        let project = |hooks: Value| -> Project {
+            let mut row = json!({ "key": "dqotd", "port": 3001 });
+            row.as_object_mut().unwrap().extend(hooks.as_object().unwrap().clone());
+            serde_json::from_value(row).unwrap()
+        };
+
+        let mut ok = project(json!({ "o2StartKey": "dqotd.start", "o2SnapshotKey": "", "o2CommitKey": " " }));
+        assert_eq!(validate_for_write(&rows, &mut ok, None), Ok(()));
+
+        for (field, verb) in [
+            ("o2StartKey", "start; rm -rf ~"),
+            ("o2SnapshotKey", "snap\nshot"),
+            ("o2CommitKey", "git commit"),
+            ("o2MapKey", "ma\tp"),
+            ("o2ProofPackKey", "proof\u{7}"),
+        ] {
+            let mut bad = project(json!({ field: verb }));
+            let err = validate_for_write(&rows, &mut bad, None).unwrap_err();
+            assert!(err.starts_with(&format!("{field}: verb ")), "{field}: {err}");
+        }
+    }
+
+    const FOUR_SPACE: &str = r#"[
+    {
+        "key": "tbis",
+        "port": 3000
+    },
+    {"key": "dqotd",   "port": 3001},
+    {
+        "port": 3002,
+        "key": "notes"
+    }
+]
+"#;
+
+    fn spliced(text: &str, edit: RowEdit) -> String {
+        let out = splice_row(text, &edit).unwrap();
+        serde_json::from_str::<Value>(&out).expect("spliced registry must parse");
+        out
+    }
+
+    #[test]
+    fn replace_touches_only_that_row() {
+        let row = json!({ "port": 3002, "key": "notes", "label": "Notes" });
+        let out = spliced(FOUR_SPACE, RowEdit::Replace(2, row));
+        assert_eq!(
+            out,
+            FOUR_SPACE.replace(
+                "        \"key\": \"notes\"\n",
+                "        \"key\": \"notes\",\n        \"label\": \"Notes\"\n"
+            )
+        );
+
+        // A one-line row stays on one line.
+        let out = spliced(FOUR_SPACE, RowEdit::Replace(1, json!({ "key": "dqotd", "port": 3005 })));
+        assert!(out.contains("    {\"key\":\"dqotd\",\"port\":3005},\n"));
+        assert!(out.starts_with("[\n    {\n        \"key\": \"tbis\",\n"));
+    }
+
+    #[test]
+    fn remove_keeps_commas_balanced() {
+        let first = spliced(FOUR_SPACE, RowEdit::Remove(0));
+        assert!(first.starts_with("[\n    {\"key\": \"dqotd\",   \"port\": 3001},\n"));
+
+        let last = spliced(FOUR_SPACE, RowEdit::Remove(2));
+        assert!(last.ends_with("    {\"key\": \"dqotd\",   \"port\": 3001}\n]\n"));
+
+        let only = spliced("[ {\"key\": \"a\"} ]\n", RowEdit::Remove(0));
+        assert_eq!(only, "[]\n");
+    }
+
+    #[test]
+    fn append_follows_the_file_layout() {
+        let out = spliced(FOUR_SPACE, RowEdit::Append(json!({ "key": "new", "port": 3003 })));
+        assert!(out.starts_with(&FOUR_SPACE[..FOUR_SPACE.len() - 3]));
+        assert!(out.ends_with("    },\n    {\n        \"key\": \"new\",\n        \"port\": 3003\n    }\n]\n"));
+
+        let inline = spliced(r#"[{"key":"a"}]"#, RowEdit::Append(json!({ "key": "b" })));
+        assert_eq!(inline, r#"[{"key":"a"}, {"key":"b"}]"#);
+
+        let empty = spliced("[]\n", RowEdit::Append(json!({ "key": "a" })));
+        assert_eq!(empty, "[\n  {\n    \"key\": \"a\"\n  }\n]\n");
+    }
+
+    #[test]
+    fn row_spans_skip_brackets_in_strings() {
+        let text = r#"[{"key": "a]", "note": "quote \" ,{"}, [1, [2]], 3]"#;
+        let spans = row_spans(text).unwrap();
+        let rows: Vec<&str> = spans.rows.iter().map(|&(s, e)| &text[s..e]).collect();
+        assert_eq!(rows, [r#"{"key": "a]", "note": "quote \" ,{"}"#, "[1, [2]]", "3"]);
+        assert_eq!((spans.open, spans.close), (0, text.len() - 1));
+    }
 }
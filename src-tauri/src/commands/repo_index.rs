@@ -0,0 +1,19 @@
+use super::registry::{load_registry, o2_root};
+use crate::repo_index::{load_profile, write_index, IndexSummary};
+
+/// Write `docs/_o2_repo_index.{txt,json}` into the project's repo using its
+/// index profile (see `repo_index::load_profile`).
+#[tauri::command(async)]
+pub fn o2_index_project(key: String) -> Result<IndexSummary, String> {
+    let key = key.trim();
+    let reg = load_registry()?;
+    let project = reg
+        .projects
+        .iter()
+        .find(|p| p.key == key)
+        .ok_or_else(|| format!("unknown project: {key}"))?;
+
+    let repo = project.repo_dir()?;
+    let (profile, source) = load_profile(&o2_root()?, &project.key, &repo)?;
+    write_index(&project.label, &repo, &profile, &source)
+}
@@ -0,0 +1,361 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::State;
+
+use super::jobs::now_ms;
+use super::ports::port_status;
+use super::registry::Project;
+use super::supervisor::{ProcState, Supervisor};
+use crate::procfs::{self, ProcStat};
+
+// Sampler interval and how many samples each project keeps (10 minutes).
+pub const SAMPLE_INTERVAL_MS: u64 = 5000;
+const HISTORY_LEN: usize = 120;
+
+/// Totals over one project's process tree at one moment.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceSample {
+    pub at_ms: u64,
+    pub pids: usize,
+    /// Summed over the tree, so it can exceed 100 on several cores. None on
+    /// the first sample of a tree, which has nothing to diff against.
+    pub cpu_percent: Option<f64>,
+    pub rss_bytes: u64,
+    pub threads: u64,
+    /// Only counts processes whose fd table we may read.
+    pub fds: u64,
+    /// Age of the tree's root process.
+    pub uptime_ms: Option<u64>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectResources {
+    pub key: String,
+    /// Supervised process, else the process listening on the project port.
+    pub root_pid: Option<u32>,
+    /// None when nothing is running for the project.
+    pub current: Option<ResourceSample>,
+    /// Oldest first; empty unless asked for.
+    pub history: Vec<ResourceSample>,
+}
+
+/// CPU ticks seen for a pid last round, keyed with its start time so a
+/// reused pid isn't diffed against its predecessor.
+#[derive(Clone, Copy)]
+struct CpuMark {
+    starttime: u64,
+    ticks: u64,
+    at: Instant,
+}
+
+#[derive(Default)]
+struct Inner {
+    history: HashMap<String, VecDeque<ResourceSample>>,
+    roots: HashMap<String, Option<u32>>,
+    cpu: HashMap<u32, CpuMark>,
+}
+
+/// Rolling resource history per project, filled by the sampler thread (see
+/// `resource_sampler.rs`). Managed as Tauri state.
+#[derive(Clone, Default)]
+pub struct ResourceBoard {
+    inner: Arc<Mutex<Inner>>,
+}
+
+/// Root pid of each project's process tree.
+fn project_roots(projects: &[Project], supervisor: &Supervisor) -> HashMap<String, Option<u32>> {
+    let supervised: HashMap<String, u32> = supervisor
+        .list()
+        .into_iter()
+        .filter(|s| s.state == ProcState::Running)
+        .map(|s| (s.key, s.pid))
+        .collect();
+
+    let ports: Vec<u16> = projects.iter().filter_map(|p| p.port).collect();
+    let listening = port_status(&ports);
+    let owner = |port: u16| listening.iter().find(|s| s.port == port && s.listening).and_then(|s| s.pid);
+
+    projects
+        .iter()
+        .map(|p| {
+            let root = supervised.get(&p.key).copied().or_else(|| p.port.and_then(owner));
+            (p.key.clone(), root)
+        })
+        .collect()
+}
+
+/// One process of a tree as read from /proc.
+struct TreeProc {
+    pid: u32,
+    stat: ProcStat,
+    fds: u64,
+}
+
+/// What every tree in one sampling round shares.
+struct Round {
+    now: Instant,
+    at_ms: u64,
+    page: u64,
+    hz: f64,
+}
+
+/// Sum one tree into a sample. CPU is diffed against `prev` only for a pid
+/// with the same start time; every process's mark goes into `next`.
+/// `uptime_ms` is left for the caller.
+fn tree_sample(
+    procs: &[TreeProc],
+    round: &Round,
+    prev: &HashMap<u32, CpuMark>,
+    next: &mut HashMap<u32, CpuMark>,
+) -> ResourceSample {
+    let mut s = ResourceSample {
+        at_ms: round.at_ms,
+        pids: 0,
+        cpu_percent: None,
+        rss_bytes: 0,
+        threads: 0,
+        fds: 0,
+        uptime_ms: None,
+    };
+    let mut cpu = 0.0;
+    let mut cpu_known = false;
+
+    for p in procs {
+        s.pids += 1;
+        s.rss_bytes += p.stat.rss_pages * round.page;
+        s.threads += p.stat.num_threads;
+        s.fds += p.fds;
+
+        let mark = CpuMark {
+            starttime: p.stat.starttime,
+            ticks: p.stat.utime + p.stat.stime,
+            at: round.now,
+        };
+        if let Some(prev) = prev.get(&p.pid).filter(|m| m.starttime == mark.starttime) {
+            let secs = round.now.duration_since(prev.at).as_secs_f64();
+            if secs > 0.0 {
+                cpu += mark.ticks.saturating_sub(prev.ticks) as f64 / round.hz / secs * 100.0;
+                cpu_known = true;
+            }
+        }
+        next.insert(p.pid, mark);
+    }
+    if cpu_known {
+        s.cpu_percent = Some((cpu * 10.0).round() / 10.0);
+    }
+    s
+}
+
+impl Inner {
+    /// Append `current` to the project's history, capped at HISTORY_LEN. A
+    /// different root is a different process; its history starts over.
+    fn record(&mut self, key: &str, root: Option<u32>, current: Option<&ResourceSample>) {
+        if self.roots.get(key).copied().flatten() != root {
+            self.history.remove(key);
+        }
+        self.roots.insert(key.to_string(), root);
+        if let Some(s) = current {
+            let h = self.history.entry(key.to_string()).or_default();
+            if h.len() >= HISTORY_LEN {
+                h.pop_front();
+            }
+            h.push_back(s.clone());
+        }
+    }
+
+    /// Forget projects that left the registry.
+    fn prune(&mut self, projects: &[Project]) {
+        self.roots.retain(|k, _| projects.iter().any(|p| &p.key == k));
+        self.history.retain(|k, _| projects.iter().any(|p| &p.key == k));
+    }
+}
+
+impl ResourceBoard {
+    /// Sample every project once and append to its history.
+    pub fn sample(&self, projects: &[Project], supervisor: &Supervisor) -> Vec<ProjectResources> {
+        let roots = project_roots(projects, supervisor);
+        let children = procfs::children_map();
+        let round = Round {
+            now: Instant::now(),
+            at_ms: now_ms(),
+            page: procfs::page_size(),
+            hz: procfs::clock_ticks_per_sec() as f64,
+        };
+
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let mut next_cpu = HashMap::new();
+        let mut out = Vec::new();
+
+        for p in projects {
+            let root = roots.get(&p.key).copied().flatten();
+            let current = root.map(|root| {
+                let procs: Vec<TreeProc> = procfs::process_tree(&[root], &children)
+                    .into_iter()
+                    .filter_map(|pid| {
+                        Some(TreeProc {
+                            pid,
+                            stat: procfs::stat(pid)?,
+                            fds: procfs::fd_count(pid).unwrap_or(0),
+                        })
+                    })
+                    .collect();
+                let mut s = tree_sample(&procs, &round, &inner.cpu, &mut next_cpu);
+                s.uptime_ms = procs
+                    .iter()
+                    .find(|t| t.pid == root)
+                    .and_then(|t| procfs::start_time_ms(t.stat.starttime))
+                    .map(|t| round.at_ms.saturating_sub(t));
+                s
+            });
+
+            inner.record(&p.key, root, current.as_ref());
+            out.push(ProjectResources {
+                key: p.key.clone(),
+                root_pid: root,
+                current,
+                history: Vec::new(),
+            });
+        }
+
+        inner.cpu = next_cpu;
+        inner.prune(projects);
+        out
+    }
+
+    /// Latest sample per project, with the rolling history when asked.
+    pub fn snapshot(&self, keys: Option<&[String]>, with_history: bool) -> Vec<ProjectResources> {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out: Vec<ProjectResources> = inner
+            .roots
+            .iter()
+            .filter(|(k, _)| keys.is_none_or(|keys| keys.contains(k)))
+            .map(|(k, root)| {
+                let h = inner.history.get(k);
+                ProjectResources {
+                    key: k.clone(),
+                    root_pid: *root,
+                    current: root.and(h.and_then(|h| h.back().cloned())),
+                    history: if with_history {
+                        h.map(|h| h.iter().cloned().collect()).unwrap_or_default()
+                    } else {
+                        Vec::new()
+                    },
+                }
+            })
+            .collect();
+        out.sort_by(|a, b| a.key.cmp(&b.key));
+        out
+    }
+}
+
+/// Resource usage from the last sampling round (every SAMPLE_INTERVAL_MS).
+#[tauri::command]
+pub fn o2_resource_usage(
+    board: State<'_, ResourceBoard>,
+    keys: Option<Vec<String>>,
+    history: Option<bool>,
+) -> Vec<ProjectResources> {
+    board.snapshot(keys.as_deref(), history.unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn proc(pid: u32, starttime: u64, ticks: u64) -> TreeProc {
+        TreeProc {
+            pid,
+            stat: ProcStat {
+                state: 'S',
+                ppid: 1,
+                pgrp: pid,
+                utime: ticks,
+                stime: 0,
+                num_threads: 2,
+                starttime,
+                rss_pages: 10,
+            },
+            fds: 3,
+        }
+    }
+
+    fn round(now: Instant) -> Round {
+        Round {
+            now,
+            at_ms: 0,
+            page: 4096,
+            hz: 100.0,
+        }
+    }
+
+    fn sample(n: u64) -> ResourceSample {
+        ResourceSample {
+            at_ms: n,
+            pids: 1,
+            cpu_percent: None,
+            rss_bytes: 0,
+            threads: 1,
+            fds: 0,
+            uptime_ms: None,
+        }
+    }
+
+    fn project(key: &str) -> Project {
+        serde_json::from_value(serde_json::json!({ "key": key })).unwrap()
+    }
+
+    #[test]
+    fn tree_totals_and_cpu_only_against_the_same_process() {
+        let t0 = Instant::now();
+        let t1 = t0 + Duration::from_secs(2);
+        let mut marks = HashMap::new();
+        let first = tree_sample(&[proc(10, 500, 100), proc(11, 600, 0)], &round(t0), &HashMap::new(), &mut marks);
+        assert_eq!(first.cpu_percent, None);
+        assert_eq!((first.pids, first.rss_bytes, first.threads, first.fds), (2, 2 * 10 * 4096, 4, 6));
+
+        // pid 10 used 100 ticks (1s) over 2s; pid 11 was reused by a new
+        // process whose ticks must not be diffed against the old one.
+        let mut next = HashMap::new();
+        let second = tree_sample(&[proc(10, 500, 200), proc(11, 900, 5000)], &round(t1), &marks, &mut next);
+        assert_eq!(second.cpu_percent, Some(50.0));
+        assert_eq!(next[&11].starttime, 900);
+
+        // Nothing comparable at all: unknown, not zero.
+        let third = tree_sample(&[proc(12, 700, 10)], &round(t1), &marks, &mut HashMap::new());
+        assert_eq!(third.cpu_percent, None);
+    }
+
+    #[test]
+    fn history_is_capped_and_restarts_with_a_new_root() {
+        let mut inner = Inner::default();
+        for n in 0..HISTORY_LEN as u64 + 5 {
+            inner.record("web", Some(100), Some(&sample(n)));
+        }
+        let h = &inner.history["web"];
+        assert_eq!(h.len(), HISTORY_LEN);
+        assert_eq!(h.front().map(|s| s.at_ms), Some(5));
+
+        inner.record("web", Some(200), Some(&sample(999)));
+        assert_eq!(inner.history["web"].len(), 1);
+
+        // Stopped: no sample, and the old history goes with the old root.
+        inner.record("web", None, None);
+        assert!(!inner.history.contains_key("web"));
+        assert_eq!(inner.roots["web"], None);
+    }
+
+    #[test]
+    fn removed_projects_are_pruned() {
+        let mut inner = Inner::default();
+        inner.record("web", Some(100), Some(&sample(1)));
+        inner.record("api", Some(200), Some(&sample(1)));
+        inner.prune(&[project("api")]);
+        assert_eq!(inner.roots.keys().collect::<Vec<_>>(), ["api"]);
+        assert_eq!(inner.history.keys().collect::<Vec<_>>(), ["api"]);
+    }
+}
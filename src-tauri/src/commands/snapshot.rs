@@ -0,0 +1,17 @@
+use super::registry::load_registry;
+use crate::snapshot::{write_snapshot, SnapshotSummary};
+
+/// Write `docs/_repo_snapshot.txt` into the project's repo natively, without
+/// needing the repo to ship its own snapshot script.
+#[tauri::command(async)]
+pub fn o2_snapshot_project(key: String) -> Result<SnapshotSummary, String> {
+    let key = key.trim();
+    let reg = load_registry()?;
+    let project = reg
+        .projects
+        .iter()
+        .find(|p| p.key == key)
+        .ok_or_else(|| format!("unknown project: {key}"))?;
+
+    write_snapshot(&project.label, &project.repo_dir()?)
+}
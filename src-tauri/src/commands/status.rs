@@ -0,0 +1,279 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+use tauri::State;
+
+use super::health::{check_projects, HealthStatus, HEALTH_TIMEOUT};
+use super::ports::{port_status, PortStatus};
+use super::registry::{load_registry, Project};
+
+// Poller interval; RADCONTROL_POLL_MS overrides the default at startup.
+pub const DEFAULT_POLL_MS: u64 = 3000;
+pub const MIN_POLL_MS: u64 = 500;
+pub const MAX_POLL_MS: u64 = 60_000;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectState {
+    NoPort,
+    Stopped,
+    Running,
+    /// Listening, but the health check failed.
+    Unhealthy,
+}
+
+/// Port and health folded together for one registry row.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectStatus {
+    pub key: String,
+    pub state: ProjectState,
+    pub port: Option<PortStatus>,
+    pub health: Option<HealthStatus>,
+}
+
+/// Probe every project: one /proc pass for ports, then HTTP checks for the
+/// ones that are listening and have a url.
+pub fn project_statuses(projects: &[Project]) -> Vec<ProjectStatus> {
+    let ports: Vec<u16> = projects.iter().filter_map(|p| p.port).collect();
+    let port_states = port_status(&ports);
+    let port_of = |port: u16| port_states.iter().find(|s| s.port == port).cloned();
+
+    let live: Vec<Project> = projects
+        .iter()
+        .filter(|p| p.port.and_then(port_of).is_some_and(|s| s.listening))
+        .cloned()
+        .collect();
+    let health = check_projects(&live, HEALTH_TIMEOUT);
+
+    projects
+        .iter()
+        .map(|p| {
+            let port = p.port.and_then(port_of);
+            let health = health.iter().find(|h| h.key == p.key).cloned();
+
+            ProjectStatus {
+                key: p.key.clone(),
+                state: fold_state(port.as_ref(), health.as_ref()),
+                port,
+                health,
+            }
+        })
+        .collect()
+}
+
+fn fold_state(port: Option<&PortStatus>, health: Option<&HealthStatus>) -> ProjectState {
+    match (port, health) {
+        (None, _) => ProjectState::NoPort,
+        (Some(s), _) if !s.listening => ProjectState::Stopped,
+        (Some(_), Some(h)) if h.checked && !h.ok => ProjectState::Unhealthy,
+        (Some(_), _) => ProjectState::Running,
+    }
+}
+
+/// A project whose state changed between two polls. `from` is None the
+/// first time a project is seen.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusTransition {
+    pub key: String,
+    pub from: Option<ProjectState>,
+    pub to: ProjectState,
+}
+
+/// Last known status per project, kept by the background poller (see
+/// `status_poller.rs`) and managed as Tauri state.
+#[derive(Clone)]
+pub struct StatusBoard {
+    statuses: Arc<Mutex<HashMap<String, ProjectStatus>>>,
+    /// Poll interval in ms; the condvar wakes the poller when it changes.
+    poll_ms: Arc<(Mutex<u64>, Condvar)>,
+}
+
+impl Default for StatusBoard {
+    fn default() -> Self {
+        let poll_ms = std::env::var("RADCONTROL_POLL_MS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(DEFAULT_POLL_MS)
+            .clamp(MIN_POLL_MS, MAX_POLL_MS);
+
+        StatusBoard {
+            statuses: Arc::default(),
+            poll_ms: Arc::new((Mutex::new(poll_ms), Condvar::new())),
+        }
+    }
+}
+
+impl StatusBoard {
+    pub fn poll_ms(&self) -> u64 {
+        *self.poll_ms.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn set_poll_ms(&self, ms: u64) -> u64 {
+        let ms = ms.clamp(MIN_POLL_MS, MAX_POLL_MS);
+        let (lock, changed) = &*self.poll_ms;
+        *lock.lock().unwrap_or_else(|e| e.into_inner()) = ms;
+        changed.notify_all();
+        ms
+    }
+
+    /// Sleep for one poll interval, or until the interval is changed so a
+    /// shorter one takes effect right away.
+    pub fn wait_for_next_poll(&self) {
+        let (lock, changed) = &*self.poll_ms;
+        let current = lock.lock().unwrap_or_else(|e| e.into_inner());
+        let ms = *current;
+        let _ = changed.wait_timeout_while(current, Duration::from_millis(ms), |now| *now == ms);
+    }
+
+    /// Registry order is not kept; sorted by key for stable output.
+    pub fn snapshot(&self) -> Vec<ProjectStatus> {
+        let statuses = self.statuses.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out: Vec<ProjectStatus> = statuses.values().cloned().collect();
+        out.sort_by(|a, b| a.key.cmp(&b.key));
+        out
+    }
+
+    /// Replace the board with `next` and report which projects changed state.
+    /// Projects that disappeared from the registry are dropped silently.
+    pub fn update(&self, next: Vec<ProjectStatus>) -> Vec<StatusTransition> {
+        let mut statuses = self.statuses.lock().unwrap_or_else(|e| e.into_inner());
+
+        let transitions = next
+            .iter()
+            .filter_map(|s| {
+                let from = statuses.get(&s.key).map(|old| old.state);
+                (from != Some(s.state)).then(|| StatusTransition {
+                    key: s.key.clone(),
+                    from,
+                    to: s.state,
+                })
+            })
+            .collect();
+
+        *statuses = next.into_iter().map(|s| (s.key.clone(), s)).collect();
+        transitions
+    }
+}
+
+/// Last state recorded by the poller; empty until the first poll finishes.
+#[tauri::command]
+pub fn o2_status_snapshot(board: State<'_, StatusBoard>) -> Vec<ProjectStatus> {
+    board.snapshot()
+}
+
+/// Change the poll interval (ms); returns the value actually applied.
+#[tauri::command]
+pub fn o2_set_poll_interval(board: State<'_, StatusBoard>, ms: u64) -> u64 {
+    board.set_poll_ms(ms)
+}
+
+#[tauri::command(async)]
+pub fn o2_project_status() -> Result<Vec<ProjectStatus>, String> {
+    let reg = load_registry()?;
+    Ok(project_statuses(&reg.projects))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Instant;
+
+    fn port(listening: bool) -> PortStatus {
+        PortStatus {
+            port: 3000,
+            listening,
+            pid: None,
+            cmd: None,
+            err: None,
+        }
+    }
+
+    fn health(checked: bool, ok: bool) -> HealthStatus {
+        HealthStatus {
+            key: "web".to_string(),
+            url: "http://127.0.0.1:3000".to_string(),
+            ok,
+            checked,
+            status: None,
+            latency_ms: None,
+            body_matched: None,
+            err: None,
+        }
+    }
+
+    fn status(key: &str, state: ProjectState) -> ProjectStatus {
+        ProjectStatus {
+            key: key.to_string(),
+            state,
+            port: None,
+            health: None,
+        }
+    }
+
+    #[test]
+    fn port_and_health_fold_into_one_state() {
+        assert_eq!(fold_state(None, None), ProjectState::NoPort);
+        assert_eq!(fold_state(Some(&port(false)), None), ProjectState::Stopped);
+        // A stale failing check doesn't make a stopped project unhealthy.
+        assert_eq!(fold_state(Some(&port(false)), Some(&health(true, false))), ProjectState::Stopped);
+        assert_eq!(fold_state(Some(&port(true)), None), ProjectState::Running);
+        assert_eq!(fold_state(Some(&port(true)), Some(&health(true, true))), ProjectState::Running);
+        assert_eq!(fold_state(Some(&port(true)), Some(&health(true, false))), ProjectState::Unhealthy);
+        // Unprobeable urls (https) never count against the project.
+        assert_eq!(fold_state(Some(&port(true)), Some(&health(false, false))), ProjectState::Running);
+    }
+
+    #[test]
+    fn update_reports_only_changes() {
+        let board = StatusBoard::default();
+        let changes = |t: Vec<StatusTransition>| -> Vec<(String, Option<ProjectState>, ProjectState)> {
+            t.into_iter().map(|t| (t.key, t.from, t.to)).collect()
+        };
+
+        let first = board.update(vec![status("api", ProjectState::Stopped), status("web", ProjectState::Running)]);
+        assert_eq!(
+            changes(first),
+            [
+                ("api".to_string(), None, ProjectState::Stopped),
+                ("web".to_string(), None, ProjectState::Running),
+            ]
+        );
+
+        // api comes up, web goes unhealthy.
+        let second = board.update(vec![status("api", ProjectState::Running), status("web", ProjectState::Unhealthy)]);
+        assert_eq!(
+            changes(second),
+            [
+                ("api".to_string(), Some(ProjectState::Stopped), ProjectState::Running),
+                ("web".to_string(), Some(ProjectState::Running), ProjectState::Unhealthy),
+            ]
+        );
+
+        // Nothing changed; web dropped from the registry is not a transition.
+        assert!(board.update(vec![status("api", ProjectState::Running)]).is_empty());
+        assert_eq!(board.snapshot().len(), 1);
+
+        // Re-added, it is new again.
+        let back = board.update(vec![status("api", ProjectState::Running), status("web", ProjectState::Stopped)]);
+        assert_eq!(changes(back), [("web".to_string(), None, ProjectState::Stopped)]);
+    }
+
+    #[test]
+    fn interval_is_clamped_and_wakes_the_poller() {
+        let board = StatusBoard::default();
+        assert_eq!(board.set_poll_ms(1), MIN_POLL_MS);
+        assert_eq!(board.set_poll_ms(u64::MAX), MAX_POLL_MS);
+
+        let waiter = board.clone();
+        let started = Instant::now();
+        let t = thread::spawn(move || waiter.wait_for_next_poll());
+        thread::sleep(Duration::from_millis(100));
+        board.set_poll_ms(1000);
+        t.join().unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,625 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom};
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+
+use super::jobs::now_ms;
+use super::logs::{file_stem, LogStore};
+use super::ports::port_status;
+use super::registry::{load_registry, Project};
+use crate::fsutil::write_atomic;
+use crate::{process, procfs};
+
+pub const SUPERVISOR_CHANGED_EVENT: &str = "supervisor-changed";
+
+// Under the app data dir; lists the processes that were running.
+const STATE_FILE: &str = "supervisor.json";
+// Under the app data dir; children write stdout/stderr here, not to pipes,
+// so they can outlive the app without dying of SIGPIPE.
+const CAPTURE_DIR: &str = "supervisor";
+// A capture file this large is truncated once it has been read to the end.
+const CAPTURE_MAX_BYTES: u64 = 8 * 1024 * 1024;
+// Output without a newline is logged in pieces of at most this size.
+const MAX_PARTIAL_LINE_BYTES: usize = 64 * 1024;
+
+const WATCH_POLL: Duration = Duration::from_millis(100);
+// Adopted processes aren't our children; poll for their group instead.
+const ADOPTED_POLL: Duration = Duration::from_secs(1);
+const STOP_GRACE: Duration = Duration::from_secs(5);
+const KILL_SETTLE: Duration = Duration::from_secs(2);
+const DRAIN_AFTER_EXIT: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcState {
+    Running,
+    Stopping,
+    Exited,
+}
+
+/// A dev server started from a registry row's `start` spec.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SupervisedProcess {
+    pub key: String,
+    /// Also the process group id.
+    pub pid: u32,
+    pub command: Vec<String>,
+    pub cwd: String,
+    pub port: Option<u16>,
+    /// Start time of `pid` from /proc; tells a reused pid apart after a restart.
+    pub proc_started_at_ms: Option<u64>,
+    pub started_at_ms: u64,
+    pub state: ProcState,
+    pub exit_code: Option<i32>,
+    pub ended_at_ms: Option<u64>,
+    /// Picked up from the state file after an app restart; output written
+    /// while no app was running isn't logged.
+    #[serde(default)]
+    pub adopted: bool,
+    /// Whether `port` is held by this process group. Refreshed on list.
+    #[serde(default, skip_deserializing)]
+    pub port_owned: Option<bool>,
+}
+
+/// Owns project dev servers. Children run in their own process group and
+/// outlive the app on purpose: the state file lets the next launch re-adopt
+/// them instead of starting duplicates. Output goes to capture files that are
+/// tailed into the project's log (see `logs.rs`). Managed as Tauri state.
+#[derive(Clone)]
+pub struct Supervisor {
+    entries: Arc<Mutex<HashMap<String, SupervisedProcess>>>,
+    state_path: PathBuf,
+    capture_dir: PathBuf,
+    app: AppHandle,
+    logs: LogStore,
+}
+
+/// Whether the process listening on `port` belongs to group `pgid`; None
+/// when nothing listens or the owner can't be inspected.
+fn port_owned_by(port: u16, pgid: u32) -> Option<bool> {
+    let st = port_status(&[port]).into_iter().next()?;
+    if !st.listening {
+        return None;
+    }
+    Some(procfs::stat(st.pid?)?.pgrp == pgid)
+}
+
+fn capture_path(dir: &Path, key: &str, stream: &str) -> PathBuf {
+    dir.join(format!("{}.{stream}.log", file_stem(key)))
+}
+
+/// Follows one capture file, handing out complete lines.
+struct Tail {
+    path: PathBuf,
+    stream: &'static str,
+    offset: u64,
+    partial: Vec<u8>,
+    /// (dev, inode) of the file `offset` refers to.
+    file_id: Option<(u64, u64)>,
+}
+
+impl Tail {
+    /// Start at the current end of the file, or at its beginning.
+    fn new(path: PathBuf, stream: &'static str, from_end: bool) -> Self {
+        let meta = fs::metadata(&path).ok();
+        let offset = if from_end { meta.as_ref().map_or(0, |m| m.len()) } else { 0 };
+        Tail {
+            path,
+            stream,
+            offset,
+            partial: Vec::new(),
+            file_id: meta.map(|m| (m.dev(), m.ino())),
+        }
+    }
+
+    /// Read whatever was appended since the last poll.
+    fn poll(&mut self, mut emit: impl FnMut(&'static str, &str)) {
+        let Ok(mut f) = File::open(&self.path) else {
+            return;
+        };
+        let Ok(meta) = f.metadata() else {
+            return;
+        };
+        let (len, id) = (meta.len(), (meta.dev(), meta.ino()));
+        // Replaced (a restart recreates it) or truncated underneath us; a
+        // replacement may already be longer than `offset`, so check both.
+        if self.file_id.is_some_and(|old| old != id) || len < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+        }
+        self.file_id = Some(id);
+        if len == self.offset || f.seek(SeekFrom::Start(self.offset)).is_err() {
+            return;
+        }
+        let mut buf = Vec::new();
+        let Ok(n) = f.take(len - self.offset).read_to_end(&mut buf) else {
+            return;
+        };
+        self.offset += n as u64;
+
+        self.partial.extend_from_slice(&buf);
+        if let Some(end) = self.partial.iter().rposition(|&b| b == b'\n') {
+            let complete: Vec<u8> = self.partial.drain(..=end).collect();
+            for line in complete[..end].split(|&b| b == b'\n') {
+                emit(self.stream, &String::from_utf8_lossy(line));
+            }
+        }
+        if self.partial.len() >= MAX_PARTIAL_LINE_BYTES {
+            emit(self.stream, &String::from_utf8_lossy(&self.partial));
+            self.partial.clear();
+        }
+
+        // Children append (O_APPEND), so cutting the file back is safe; a
+        // line written between the read above and here is lost.
+        if self.offset >= CAPTURE_MAX_BYTES && self.partial.is_empty() {
+            if let Ok(f) = OpenOptions::new().write(true).open(&self.path) {
+                if f.set_len(0).is_ok() {
+                    self.offset = 0;
+                }
+            }
+        }
+    }
+
+    /// Emit a trailing line that never got its newline.
+    fn flush(&mut self, mut emit: impl FnMut(&'static str, &str)) {
+        if !self.partial.is_empty() {
+            emit(self.stream, &String::from_utf8_lossy(&self.partial));
+            self.partial.clear();
+        }
+    }
+}
+
+/// A fresh, append-only capture file for one of the child's streams.
+fn open_capture(path: &Path) -> Result<File, String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    let _ = fs::remove_file(path);
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {e}", path.display()))
+}
+
+/// Why an existing entry keeps `start` from launching another copy, if it
+/// does. A Stopping entry whose group is already gone doesn't count.
+fn blocking_state(e: &SupervisedProcess, group_alive: impl Fn(u32) -> bool) -> Option<&'static str> {
+    match e.state {
+        ProcState::Exited => None,
+        ProcState::Stopping if !group_alive(e.pid) => None,
+        ProcState::Stopping => Some("stopping"),
+        ProcState::Running => Some("running"),
+    }
+}
+
+fn spawn_child(project: &Project, capture_dir: &Path) -> Result<(Child, Vec<String>, PathBuf), String> {
+    let spec = project
+        .start
+        .as_ref()
+        .ok_or_else(|| format!("project {} has no start command", project.key))?;
+    let cwd = project.start_dir()?;
+    if !cwd.is_dir() {
+        return Err(format!("start directory is not a directory: {}", cwd.display()));
+    }
+
+    let stdout = open_capture(&capture_path(capture_dir, &project.key, "stdout"))?;
+    let stderr = open_capture(&capture_path(capture_dir, &project.key, "stderr"))?;
+
+    let child = Command::new(&spec.command[0])
+        .args(&spec.command[1..])
+        .current_dir(&cwd)
+        .envs(&spec.env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::from(stdout))
+        .stderr(Stdio::from(stderr))
+        // Own process group, so stop reaches everything the server forks.
+        .process_group(0)
+        .spawn()
+        .map_err(|e| format!("failed to start {}: {e}", spec.command[0]))?;
+    Ok((child, spec.command.clone(), cwd))
+}
+
+impl Supervisor {
+    pub fn new(data_dir: &Path, app: AppHandle, logs: LogStore) -> Self {
+        Supervisor {
+            entries: Arc::default(),
+            state_path: data_dir.join(STATE_FILE),
+            capture_dir: data_dir.join(CAPTURE_DIR),
+            app,
+            logs,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, SupervisedProcess>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Rewrite the state file from `entries`; exited processes aren't kept.
+    fn persist(&self, entries: &HashMap<String, SupervisedProcess>) {
+        let mut live: Vec<&SupervisedProcess> = entries
+            .values()
+            .filter(|i| i.state != ProcState::Exited)
+            .collect();
+        live.sort_by(|a, b| a.key.cmp(&b.key));
+        if let Ok(json) = serde_json::to_vec_pretty(&live) {
+            let _ = write_atomic(&self.state_path, &json);
+        }
+    }
+
+    /// Apply `f` to the entry for `key` if it is still `pid`, then persist
+    /// and notify. Returns the updated info.
+    fn update<F>(&self, key: &str, pid: u32, f: F) -> Option<SupervisedProcess>
+    where
+        F: FnOnce(&mut SupervisedProcess),
+    {
+        let mut entries = self.lock();
+        let info = entries.get_mut(key).filter(|i| i.pid == pid)?;
+        f(info);
+        let info = info.clone();
+        self.persist(&entries);
+        drop(entries);
+
+        let _ = self.app.emit(SUPERVISOR_CHANGED_EVENT, &info);
+        Some(info)
+    }
+
+    fn mark_exited(&self, key: &str, pid: u32, code: Option<i32>) {
+        let mut changed = false;
+        self.update(key, pid, |i| {
+            if i.state != ProcState::Exited {
+                i.state = ProcState::Exited;
+                i.exit_code = code;
+                i.ended_at_ms = Some(now_ms());
+                changed = true;
+            }
+        });
+        if changed {
+            let code = code.map(|c| format!("code {c}")).unwrap_or_else(|| "no exit code".to_string());
+            self.logs.append(key, "supervisor", &format!("pid {pid} exited ({code})"), None);
+        }
+    }
+
+    fn tails(&self, key: &str, from_end: bool) -> [Tail; 2] {
+        ["stdout", "stderr"].map(|stream| Tail::new(capture_path(&self.capture_dir, key, stream), stream, from_end))
+    }
+
+    fn pump(&self, key: &str, tails: &mut [Tail]) {
+        for t in tails.iter_mut() {
+            t.poll(|stream, line| self.logs.append(key, stream, line, None));
+        }
+    }
+
+    fn drain(&self, key: &str, tails: &mut [Tail]) {
+        self.pump(key, tails);
+        for t in tails.iter_mut() {
+            t.flush(|stream, line| self.logs.append(key, stream, line, None));
+        }
+    }
+
+    /// Tail output and reap the child; runs on its own thread until exit.
+    fn watch_child(&self, key: String, mut child: Child) {
+        let pid = child.id();
+        let mut tails = self.tails(&key, false);
+
+        let mut exited: Option<(Option<i32>, Instant)> = None;
+        loop {
+            self.pump(&key, &mut tails);
+            if exited.is_none() {
+                match child.try_wait() {
+                    Ok(Some(s)) => exited = Some((s.code(), Instant::now())),
+                    Ok(None) => {}
+                    Err(_) => exited = Some((None, Instant::now())),
+                }
+            }
+            // Grandchildren may still write for a moment after the leader exits.
+            if exited.is_some_and(|(_, t)| t.elapsed() >= DRAIN_AFTER_EXIT) {
+                break;
+            }
+            thread::sleep(WATCH_POLL);
+        }
+        self.drain(&key, &mut tails);
+        self.mark_exited(&key, pid, exited.and_then(|(c, _)| c));
+    }
+
+    /// Adopted processes aren't our children: follow their capture files from
+    /// where they are now and poll for the group instead of reaping.
+    fn watch_adopted(&self, key: String, pid: u32) {
+        let mut tails = self.tails(&key, true);
+        let mut last_check = Instant::now();
+        loop {
+            self.pump(&key, &mut tails);
+            if last_check.elapsed() >= ADOPTED_POLL {
+                if !process::group_exists(pid) {
+                    break;
+                }
+                last_check = Instant::now();
+            }
+            thread::sleep(WATCH_POLL);
+        }
+        self.drain(&key, &mut tails);
+        self.mark_exited(&key, pid, None);
+    }
+
+    /// Start `project`'s dev server. Refuses when it is already running here
+    /// or something else already listens on its port.
+    pub fn start(&self, project: &Project) -> Result<SupervisedProcess, String> {
+        let mut entries = self.lock();
+        if let Some(e) = entries.get(&project.key) {
+            if let Some(state) = blocking_state(e, process::group_exists) {
+                return Err(format!("{} is already {state} (pid {})", project.key, e.pid));
+            }
+        }
+        if let Some(port) = project.port {
+            if let Some(st) = port_status(&[port]).into_iter().next().filter(|s| s.listening) {
+                let owner = st.pid.map(|p| format!(" by pid {p}")).unwrap_or_default();
+                return Err(format!("port {port} is already in use{owner}"));
+            }
+        }
+
+        let (child, command, cwd) = spawn_child(project, &self.capture_dir)?;
+        let pid = child.id();
+        let info = SupervisedProcess {
+            key: project.key.clone(),
+            pid,
+            command,
+            cwd: cwd.display().to_string(),
+            port: project.port,
+            proc_started_at_ms: procfs::stat(pid).and_then(|s| procfs::start_time_ms(s.starttime)),
+            started_at_ms: now_ms(),
+            state: ProcState::Running,
+            exit_code: None,
+            ended_at_ms: None,
+            adopted: false,
+            port_owned: None,
+        };
+        entries.insert(project.key.clone(), info.clone());
+        self.persist(&entries);
+        drop(entries);
+
+        self.logs.append(
+            &project.key,
+            "supervisor",
+            &format!("started pid {pid} in {}: {}", info.cwd, info.command.join(" ")),
+            None,
+        );
+
+        let _ = self.app.emit(SUPERVISOR_CHANGED_EVENT, &info);
+        let sup = self.clone();
+        let key = project.key.clone();
+        thread::spawn(move || sup.watch_child(key, child));
+        Ok(info)
+    }
+
+    /// SIGTERM the process group, then SIGKILL whatever is left after
+    /// STOP_GRACE. Returns once the group is gone or KILL_SETTLE has passed.
+    pub fn stop(&self, key: &str) -> Result<SupervisedProcess, String> {
+        let pid = {
+            let entries = self.lock();
+            entries
+                .get(key)
+                .filter(|i| i.state != ProcState::Exited)
+                .ok_or_else(|| format!("{key} is not running under the supervisor"))?
+                .pid
+        };
+        self.update(key, pid, |i| i.state = ProcState::Stopping);
+        self.logs.append(key, "supervisor", &format!("stopping process group {pid}"), None);
+
+        process::signal_group(pid, process::SIGTERM);
+        let deadline = Instant::now() + STOP_GRACE;
+        while process::group_exists(pid) && Instant::now() < deadline {
+            thread::sleep(WATCH_POLL);
+        }
+        if process::group_exists(pid) {
+            process::signal_group(pid, process::SIGKILL);
+            let deadline = Instant::now() + KILL_SETTLE;
+            while process::group_exists(pid) && Instant::now() < deadline {
+                thread::sleep(WATCH_POLL);
+            }
+        }
+
+        // The watcher thread records the exit code for children we own; let it.
+        let deadline = Instant::now() + DRAIN_AFTER_EXIT + WATCH_POLL * 2;
+        while Instant::now() < deadline {
+            if self.get(key).is_some_and(|i| i.pid != pid || i.state == ProcState::Exited) {
+                break;
+            }
+            thread::sleep(WATCH_POLL);
+        }
+        if process::group_exists(pid) {
+            // Still there, so still running: don't leave it stuck in Stopping.
+            self.update(key, pid, |i| {
+                if i.state == ProcState::Stopping {
+                    i.state = ProcState::Running;
+                }
+            });
+            self.logs.append(key, "supervisor", &format!("process group {pid} survived SIGKILL"), None);
+            return Err(format!("{key}: process group {pid} survived SIGKILL"));
+        }
+        self.mark_exited(key, pid, None);
+        self.get(key).ok_or_else(|| format!("{key} disappeared while stopping"))
+    }
+
+    pub fn restart(&self, project: &Project) -> Result<SupervisedProcess, String> {
+        if self.get(&project.key).is_some_and(|i| i.state != ProcState::Exited) {
+            self.stop(&project.key)?;
+        }
+        self.start(project)
+    }
+
+    pub fn get(&self, key: &str) -> Option<SupervisedProcess> {
+        self.lock().get(key).cloned()
+    }
+
+    /// Every supervised process, including ones that exited this session.
+    pub fn list(&self) -> Vec<SupervisedProcess> {
+        let mut out: Vec<SupervisedProcess> = self.lock().values().cloned().collect();
+        for i in out.iter_mut().filter(|i| i.state == ProcState::Running) {
+            i.port_owned = i.port.and_then(|p| port_owned_by(p, i.pid));
+        }
+        out.sort_by(|a, b| a.key.cmp(&b.key));
+        out
+    }
+
+    /// Re-adopt processes recorded by a previous run whose group is still
+    /// alive, and check their ports against what is actually listening. A
+    /// leader pid that now belongs to a different process (reuse after a
+    /// reboot) is dropped.
+    pub fn reconcile(&self) {
+        let recorded: Vec<SupervisedProcess> = fs::read_to_string(&self.state_path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+
+        let mut adopted = Vec::new();
+        {
+            let mut entries = self.lock();
+            for mut info in recorded {
+                if !process::group_exists(info.pid) {
+                    continue;
+                }
+                let leader_start = procfs::stat(info.pid).and_then(|s| procfs::start_time_ms(s.starttime));
+                if leader_start.is_some() && info.proc_started_at_ms.is_some() && leader_start != info.proc_started_at_ms {
+                    continue;
+                }
+                info.port_owned = info.port.and_then(|p| port_owned_by(p, info.pid));
+                info.state = ProcState::Running;
+                info.adopted = true;
+                adopted.push((info.key.clone(), info.pid));
+                entries.insert(info.key.clone(), info);
+            }
+            self.persist(&entries);
+        }
+
+        for (key, pid) in adopted {
+            let sup = self.clone();
+            thread::spawn(move || sup.watch_adopted(key, pid));
+        }
+    }
+}
+
+fn find_project(key: &str) -> Result<Project, String> {
+    load_registry()?
+        .projects
+        .into_iter()
+        .find(|p| p.key == key)
+        .ok_or_else(|| format!("unknown project: {key}"))
+}
+
+#[tauri::command]
+pub fn o2_supervisor_list(sup: State<'_, Supervisor>) -> Vec<SupervisedProcess> {
+    sup.list()
+}
+
+#[tauri::command(async)]
+pub fn o2_supervisor_start(sup: State<'_, Supervisor>, key: String) -> Result<SupervisedProcess, String> {
+    sup.start(&find_project(key.trim())?)
+}
+
+#[tauri::command(async)]
+pub fn o2_supervisor_stop(sup: State<'_, Supervisor>, key: String) -> Result<SupervisedProcess, String> {
+    sup.stop(key.trim())
+}
+
+#[tauri::command(async)]
+pub fn o2_supervisor_restart(sup: State<'_, Supervisor>, key: String) -> Result<SupervisedProcess, String> {
+    sup.restart(&find_project(key.trim())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn tail_hands_out_complete_lines_and_survives_truncation() {
+        let path = std::env::temp_dir().join(format!("radcontrol-tail-{}.log", std::process::id()));
+        let mut f = open_capture(&path).unwrap();
+        let mut tail = Tail::new(path.clone(), "stdout", false);
+        let mut lines = Vec::new();
+
+        f.write_all(b"one\ntw").unwrap();
+        tail.poll(|_, l| lines.push(l.to_string()));
+        assert_eq!(lines, ["one"]);
+
+        f.write_all(b"o\nthree").unwrap();
+        tail.poll(|_, l| lines.push(l.to_string()));
+        assert_eq!(lines, ["one", "two"]);
+
+        // Cut back (as when the capture file is rotated); the fd appends at 0.
+        OpenOptions::new().write(true).open(&path).unwrap().set_len(0).unwrap();
+        f.write_all(b"four\n").unwrap();
+        tail.poll(|_, l| lines.push(l.to_string()));
+        tail.flush(|_, l| lines.push(l.to_string()));
+        let _ = fs::remove_file(&path);
+        assert_eq!(lines, ["one", "two", "four"]);
+    }
+
+    #[test]
+    fn tail_from_end_skips_existing_output() {
+        let path = std::env::temp_dir().join(format!("radcontrol-tail-end-{}.log", std::process::id()));
+        let mut f = open_capture(&path).unwrap();
+        f.write_all(b"before\n").unwrap();
+        let mut tail = Tail::new(path.clone(), "stderr", true);
+        f.write_all(b"after\nlast").unwrap();
+
+        let mut lines = Vec::new();
+        tail.poll(|s, l| lines.push(format!("{s}:{l}")));
+        tail.flush(|s, l| lines.push(format!("{s}:{l}")));
+        let _ = fs::remove_file(&path);
+        assert_eq!(lines, ["stderr:after", "stderr:last"]);
+    }
+
+    #[test]
+    fn tail_restarts_on_a_replaced_file_even_when_it_is_longer() {
+        let path = std::env::temp_dir().join(format!("radcontrol-tail-replaced-{}.log", std::process::id()));
+        let mut f = open_capture(&path).unwrap();
+        let mut tail = Tail::new(path.clone(), "stdout", false);
+        let mut lines = Vec::new();
+
+        f.write_all(b"old\n").unwrap();
+        tail.poll(|_, l| lines.push(l.to_string()));
+
+        // A restart recreates the capture file; it outgrows the old offset
+        // before the next poll.
+        let mut f = open_capture(&path).unwrap();
+        f.write_all(b"new one\nnew two\n").unwrap();
+        tail.poll(|_, l| lines.push(l.to_string()));
+        let _ = fs::remove_file(&path);
+        assert_eq!(lines, ["old", "new one", "new two"]);
+    }
+
+    #[test]
+    fn only_live_entries_block_start() {
+        let entry = |state| SupervisedProcess {
+            key: "web".to_string(),
+            pid: 4242,
+            command: vec!["npm".to_string()],
+            cwd: "/tmp".to_string(),
+            port: None,
+            proc_started_at_ms: None,
+            started_at_ms: 0,
+            state,
+            exit_code: None,
+            ended_at_ms: None,
+            adopted: false,
+            port_owned: None,
+        };
+        let alive = |_| true;
+        let gone = |_| false;
+
+        assert_eq!(blocking_state(&entry(ProcState::Running), alive), Some("running"));
+        assert_eq!(blocking_state(&entry(ProcState::Stopping), alive), Some("stopping"));
+        assert_eq!(blocking_state(&entry(ProcState::Stopping), gone), None);
+        assert_eq!(blocking_state(&entry(ProcState::Exited), alive), None);
+    }
+}
@@ -0,0 +1,126 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::time::Duration;
+
+use super::registry::o2_root;
+
+// Used when neither the config file nor the built-ins match a verb.
+const DEFAULT_TIMEOUT_SECS: u64 = 600;
+
+// Built-in overrides; the config file wins over these.
+const BUILTIN_TIMEOUTS: &[(&str, u64)] = &[
+    ("port_status", 2),
+    ("port_status.*", 2),
+    ("kill_port", 15),
+    ("kill_port.*", 15),
+];
+
+/// `$O2_ROOT/registry/verb_timeouts.json`, e.g.
+/// `{ "defaultSecs": 600, "verbs": { "port_status.*": 2, "tbis.commit": 900 } }`
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct TimeoutConfig {
+    default_secs: Option<u64>,
+    #[serde(default)]
+    verbs: HashMap<String, u64>,
+}
+
+fn load_config() -> Result<TimeoutConfig, String> {
+    let root = o2_root()?;
+    let path = format!("{root}/registry/verb_timeouts.json");
+
+    if !std::path::Path::new(&path).is_file() {
+        return Ok(TimeoutConfig::default());
+    }
+
+    let s = fs::read_to_string(&path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    serde_json::from_str(&s).map_err(|e| format!("Invalid JSON in {path}: {e}"))
+}
+
+/// Patterns are either an exact verb or contain a single `*` wildcard
+/// (`port_status.*`, `*.commit`). Returns the number of literal characters
+/// matched so the most specific pattern can win.
+fn pattern_score(pattern: &str, verb: &str) -> Option<usize> {
+    match pattern.split_once('*') {
+        None => (pattern == verb).then_some(usize::MAX),
+        Some((pre, post)) => {
+            let fits = verb.len() >= pre.len() + post.len()
+                && verb.starts_with(pre)
+                && verb.ends_with(post);
+            fits.then_some(pre.len() + post.len())
+        }
+    }
+}
+
+fn best_match<'a, I>(patterns: I, verb: &str) -> Option<u64>
+where
+    I: Iterator<Item = (&'a str, u64)>,
+{
+    patterns
+        .filter_map(|(p, secs)| pattern_score(p, verb).map(|score| (score, secs)))
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, secs)| secs)
+}
+
+/// Effective timeout for `verb`: config file patterns, then built-ins, then
+/// the configured (or built-in) default. An unreadable config file falls back
+/// to built-ins rather than blocking the run.
+pub fn timeout_for_verb(verb: &str) -> Duration {
+    resolve(&load_config().unwrap_or_default(), verb)
+}
+
+fn resolve(cfg: &TimeoutConfig, verb: &str) -> Duration {
+    let secs = best_match(cfg.verbs.iter().map(|(p, s)| (p.as_str(), *s)), verb)
+        .or_else(|| best_match(BUILTIN_TIMEOUTS.iter().copied(), verb))
+        .or(cfg.default_secs)
+        .unwrap_or(DEFAULT_TIMEOUT_SECS);
+
+    Duration::from_secs(secs.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg(default_secs: Option<u64>, verbs: &[(&str, u64)]) -> TimeoutConfig {
+        TimeoutConfig {
+            default_secs,
+            verbs: verbs.iter().map(|(p, s)| (p.to_string(), *s)).collect(),
+        }
+    }
+
+    #[test]
+    fn exact_pattern_beats_wildcards() {
+        assert_eq!(pattern_score("tbis.commit", "tbis.commit"), Some(usize::MAX));
+        assert_eq!(pattern_score("tbis.commit", "tbis.commits"), None);
+        let c = cfg(None, &[("tbis.*", 30), ("*.commit", 60), ("tbis.commit", 90)]);
+        assert_eq!(resolve(&c, "tbis.commit"), Duration::from_secs(90));
+    }
+
+    #[test]
+    fn longer_literal_wildcard_wins() {
+        assert_eq!(pattern_score("port_status.*", "port_status.tbis"), Some(12));
+        assert_eq!(pattern_score("*.commit", "tbis.commit"), Some(7));
+        // Prefix and suffix may not overlap.
+        assert_eq!(pattern_score("ab*ba", "aba"), None);
+        let c = cfg(None, &[("t*", 10), ("tbis.*", 20), ("*.commit", 30)]);
+        assert_eq!(resolve(&c, "tbis.snapshot"), Duration::from_secs(20));
+        assert_eq!(resolve(&c, "tbis.commit"), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn config_then_builtins_then_default() {
+        let empty = cfg(None, &[]);
+        assert_eq!(resolve(&empty, "port_status.tbis"), Duration::from_secs(2));
+        assert_eq!(resolve(&empty, "kill_port"), Duration::from_secs(15));
+        assert_eq!(resolve(&empty, "tbis.commit"), Duration::from_secs(DEFAULT_TIMEOUT_SECS));
+
+        let c = cfg(Some(120), &[("kill_port.*", 40)]);
+        assert_eq!(resolve(&c, "kill_port.tbis"), Duration::from_secs(40));
+        // Built-ins still apply to verbs the config doesn't mention.
+        assert_eq!(resolve(&c, "kill_port"), Duration::from_secs(15));
+        assert_eq!(resolve(&c, "tbis.commit"), Duration::from_secs(120));
+        assert_eq!(resolve(&cfg(Some(0), &[]), "x"), Duration::from_secs(1));
+    }
+}
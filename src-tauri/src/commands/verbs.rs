@@ -0,0 +1,531 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+
+use super::o2::O2Args;
+use super::registry::{load_registry, o2_root, registry_path};
+
+// Used for `text` params that don't declare `maxLen`.
+const DEFAULT_TEXT_MAX_LEN: usize = 1000;
+// Registry commit hooks take the commit message as a `message` argument.
+const COMMIT_MESSAGE_MAX_LEN: usize = 4000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum ParamType {
+    /// 1-65535, given as a number or a numeric string.
+    Port,
+    /// Key of a row in the project registry.
+    ProjectKey,
+    /// Free text, bounded by `maxLen`. Newlines and tabs are fine; other
+    /// control characters are not.
+    Text,
+}
+
+/// A named argument a verb accepts; reaches run_o2.sh as `O2_ARG_<NAME>`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ParamSpec {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: ParamType,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_len: Option<usize>,
+}
+
+impl ParamSpec {
+    fn new(name: &str, kind: ParamType, required: bool, max_len: Option<usize>) -> Self {
+        ParamSpec {
+            name: name.to_string(),
+            kind,
+            required,
+            max_len,
+        }
+    }
+
+    /// Normalize one value to the string exported to the script.
+    /// `project_exists` answers for `ProjectKey` params.
+    fn check(&self, value: &Value, project_exists: &dyn Fn(&str) -> Result<bool, String>) -> Result<String, String> {
+        let name = &self.name;
+        match self.kind {
+            ParamType::Port => {
+                let port = match value {
+                    Value::Number(n) => n.as_u64(),
+                    Value::String(s) => s.trim().parse::<u64>().ok(),
+                    _ => None,
+                };
+                match port {
+                    Some(p @ 1..=65535) => Ok(p.to_string()),
+                    _ => Err(format!("{name}: expected a port between 1 and 65535")),
+                }
+            }
+            ParamType::ProjectKey => {
+                let key = value.as_str().map(str::trim).unwrap_or_default();
+                if key.is_empty() {
+                    return Err(format!("{name}: expected a project key"));
+                }
+                if !project_exists(key)? {
+                    return Err(format!("{name}: unknown project {key}"));
+                }
+                Ok(key.to_string())
+            }
+            ParamType::Text => {
+                let text = value.as_str().ok_or_else(|| format!("{name}: expected a string"))?;
+                let max = self.max_len.unwrap_or(DEFAULT_TEXT_MAX_LEN);
+                if text.chars().count() > max {
+                    return Err(format!("{name}: longer than {max} characters"));
+                }
+                if let Some(c) = text.chars().find(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t')) {
+                    return Err(format!("{name}: contains control character {:?}", c));
+                }
+                Ok(text.to_string())
+            }
+        }
+    }
+}
+
+/// Env-safe names only: they become part of `O2_ARG_<NAME>`.
+fn valid_param_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Validate `args` against a verb's declared params. Unknown names, missing
+/// required params and ill-typed values are all rejected; `null` counts as
+/// absent.
+pub fn check_args(params: &[ParamSpec], args: &Map<String, Value>) -> Result<O2Args, String> {
+    check_args_with(params, args, &|key| Ok(load_registry()?.projects.iter().any(|p| p.key == key)))
+}
+
+fn check_args_with(
+    params: &[ParamSpec],
+    args: &Map<String, Value>,
+    project_exists: &dyn Fn(&str) -> Result<bool, String>,
+) -> Result<O2Args, String> {
+    let mut out = O2Args::new();
+    for (name, value) in args {
+        let spec = params
+            .iter()
+            .find(|p| &p.name == name)
+            .ok_or_else(|| format!("unknown argument: {name}"))?;
+        if value.is_null() {
+            continue;
+        }
+        out.insert(name.clone(), spec.check(value, project_exists)?);
+    }
+
+    if let Some(missing) = params.iter().find(|p| p.required && !out.contains_key(&p.name)) {
+        return Err(format!("missing argument: {}", missing.name));
+    }
+    Ok(out)
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum VerbSource {
+    Catalog,
+    Registry,
+    Builtin,
+}
+
+/// Descriptive and safety metadata for a verb. Every field is optional so
+/// catalog entries, registry hooks and built-ins can each fill in what they
+/// know; see `allowed_verbs` for how they are merged.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VerbMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Destructive verbs need a token from o2_prepare_verb to run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destructive: Option<bool>,
+    /// Text the user must type back when confirming a destructive verb.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm_phrase: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_duration_ms: Option<u64>,
+    /// Whether the UI should re-probe ports once the verb finishes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_ports: Option<bool>,
+}
+
+impl VerbMeta {
+    fn describe(description: &str, category: &str) -> Self {
+        VerbMeta {
+            description: Some(description.to_string()),
+            category: Some(category.to_string()),
+            ..VerbMeta::default()
+        }
+    }
+
+    fn refreshing_ports(mut self) -> Self {
+        self.refresh_ports = Some(true);
+        self
+    }
+
+    fn destructive(mut self) -> Self {
+        self.destructive = Some(true);
+        self
+    }
+
+    /// Fill fields this one leaves unset from `other`.
+    fn fill_from(&mut self, other: VerbMeta) {
+        self.description = self.description.take().or(other.description);
+        self.category = self.category.take().or(other.category);
+        self.destructive = self.destructive.or(other.destructive);
+        self.confirm_phrase = self.confirm_phrase.take().or(other.confirm_phrase);
+        self.expected_duration_ms = self.expected_duration_ms.or(other.expected_duration_ms);
+        self.refresh_ports = self.refresh_ports.or(other.refresh_ports);
+    }
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VerbInfo {
+    pub verb: String,
+    pub source: VerbSource,
+    /// Registry key of the project the verb was derived from, if any.
+    pub project: Option<String>,
+    /// Named arguments run_o2 accepts for this verb; empty means none.
+    pub params: Vec<ParamSpec>,
+    #[serde(flatten)]
+    pub meta: VerbMeta,
+}
+
+impl VerbInfo {
+    fn new(verb: &str, source: VerbSource, project: Option<String>, meta: VerbMeta) -> Self {
+        VerbInfo {
+            verb: verb.trim().to_string(),
+            source,
+            project,
+            params: Vec::new(),
+            meta,
+        }
+    }
+
+    fn with_params(mut self, params: Vec<ParamSpec>) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn is_destructive(&self) -> bool {
+        self.meta.destructive == Some(true)
+    }
+}
+
+/// `$O2_ROOT/registry/verbs.json` is an array of verb names or objects with
+/// at least a `verb` field, e.g.
+/// `{ "verb": "notes.add", "description": "Append a note", "category": "notes",
+///    "params": [{ "name": "text", "type": "text", "required": true, "maxLen": 500 }] }`
+/// Objects may carry any `VerbMeta` field.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CatalogEntry {
+    Name(String),
+    Entry {
+        verb: String,
+        #[serde(default)]
+        params: Vec<ParamSpec>,
+        #[serde(flatten)]
+        meta: VerbMeta,
+    },
+}
+
+/// A verb source entry that was skipped, reported next to the verbs that
+/// loaded.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VerbError {
+    pub source: VerbSource,
+    /// Registry key for a broken hook.
+    pub project: Option<String>,
+    pub verb: Option<String>,
+    pub message: String,
+}
+
+impl VerbError {
+    fn new(source: VerbSource, project: Option<&str>, verb: Option<&str>, message: String) -> Self {
+        VerbError {
+            source,
+            project: project.map(str::to_string),
+            verb: verb.map(str::to_string),
+            message,
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VerbLoad {
+    pub verbs: Vec<VerbInfo>,
+    pub errors: Vec<VerbError>,
+}
+
+/// Verbs are passed to run_o2.sh as a single argument; reject anything that
+/// can't be one.
+pub(super) fn check_verb_name(verb: &str) -> Result<(), String> {
+    if verb.trim().is_empty() {
+        return Err("verb is empty".to_string());
+    }
+    if verb.trim().chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(format!("verb {verb:?} contains whitespace or control characters"));
+    }
+    Ok(())
+}
+
+/// Parse each catalog entry on its own so one bad entry doesn't hide the
+/// rest.
+fn catalog_verbs(errors: &mut Vec<VerbError>) -> Vec<VerbInfo> {
+    let mut fail = |verb: Option<&str>, message: String| {
+        errors.push(VerbError::new(VerbSource::Catalog, None, verb, message));
+    };
+    let path = match o2_root() {
+        Ok(root) => format!("{root}/registry/verbs.json"),
+        Err(e) => {
+            fail(None, e);
+            return Vec::new();
+        }
+    };
+
+    if !std::path::Path::new(&path).is_file() {
+        return Vec::new();
+    }
+
+    let entries = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {path}: {e}"))
+        .and_then(|s| {
+            serde_json::from_str::<Vec<Value>>(&s).map_err(|e| format!("Invalid verb catalog {path}: {e}"))
+        });
+    let entries = match entries {
+        Ok(entries) => entries,
+        Err(e) => {
+            fail(None, e);
+            return Vec::new();
+        }
+    };
+
+    let mut verbs = Vec::new();
+    for (index, entry) in entries.into_iter().enumerate() {
+        let name = entry.as_str().or_else(|| entry.get("verb")?.as_str()).map(str::to_string);
+        let info = match serde_json::from_value::<CatalogEntry>(entry) {
+            Ok(CatalogEntry::Name(v)) => VerbInfo::new(&v, VerbSource::Catalog, None, VerbMeta::default()),
+            Ok(CatalogEntry::Entry { verb, params, meta }) => {
+                VerbInfo::new(&verb, VerbSource::Catalog, None, meta).with_params(params)
+            }
+            Err(e) => {
+                fail(name.as_deref(), format!("entry {index}: {e}"));
+                continue;
+            }
+        };
+
+        if let Err(e) = check_verb_name(&info.verb) {
+            fail(name.as_deref(), format!("entry {index}: {e}"));
+            continue;
+        }
+        if let Some(bad) = info.params.iter().find(|p| !valid_param_name(&p.name)) {
+            let e = format!("parameter name {:?} must match [a-z][a-z0-9_]*", bad.name);
+            fail(name.as_deref(), format!("entry {index}: {e}"));
+            continue;
+        }
+        verbs.push(info);
+    }
+    verbs
+}
+
+fn registry_verbs(errors: &mut Vec<VerbError>) -> Vec<VerbInfo> {
+    let registry = match registry_path() {
+        Ok(path) if !std::path::Path::new(&path).is_file() => return Vec::new(),
+        Ok(_) => load_registry(),
+        Err(e) => Err(e),
+    };
+    let projects = match registry {
+        Ok(r) => r.projects,
+        Err(e) => {
+            errors.push(VerbError::new(VerbSource::Registry, None, None, e));
+            return Vec::new();
+        }
+    };
+
+    // Rows that fail validation contribute no verbs; o2_list_projects
+    // reports them. A broken hook only drops that hook.
+    let mut out = Vec::new();
+    for p in projects {
+        let key = Some(p.key.clone());
+        let label = &p.label;
+        let hooks = [
+            (&p.o2_start_key, VerbMeta::describe(&format!("Start {label}"), "start").refreshing_ports()),
+            (&p.o2_snapshot_key, VerbMeta::describe(&format!("Snapshot {label}"), "docs")),
+            (&p.o2_commit_key, VerbMeta::describe(&format!("Commit {label}"), "git")),
+            (&p.o2_map_key, VerbMeta::describe(&format!("{label} Map"), "docs")),
+            (&p.o2_proof_pack_key, VerbMeta::describe(&format!("{label} Proof Pack"), "docs")),
+        ];
+        for (verb, meta) in hooks {
+            // An empty hook is an unset one.
+            let Some(verb) = verb.as_ref().filter(|v| !v.trim().is_empty()) else {
+                continue;
+            };
+            if let Err(e) = check_verb_name(verb) {
+                errors.push(VerbError::new(VerbSource::Registry, Some(&p.key), Some(verb), e));
+                continue;
+            }
+            let mut info = VerbInfo::new(verb, VerbSource::Registry, key.clone(), meta);
+            if p.o2_commit_key.as_ref() == Some(verb) {
+                info.params = vec![ParamSpec::new("message", ParamType::Text, false, Some(COMMIT_MESSAGE_MAX_LEN))];
+            }
+            out.push(info);
+        }
+
+        if let Some(port) = p.port {
+            out.push(VerbInfo::new(
+                &format!("port_status.{port}"),
+                VerbSource::Registry,
+                key.clone(),
+                VerbMeta::describe(&format!("Probe :{port} ({label})"), "ports"),
+            ));
+            out.push(VerbInfo::new(
+                &format!("kill_port.{port}"),
+                VerbSource::Registry,
+                key.clone(),
+                VerbMeta::describe(&format!("Stop whatever listens on :{port} ({label})"), "ports")
+                    .destructive()
+                    .refreshing_ports(),
+            ));
+        }
+    }
+    out
+}
+
+/// Verbs the app itself invokes regardless of catalog/registry, plus the
+/// parameterized forms of the dotted per-port verbs (`kill_port.1420`).
+fn builtin_verbs() -> Vec<VerbInfo> {
+    let port = || vec![ParamSpec::new("port", ParamType::Port, true, None)];
+    vec![
+        VerbInfo::new(
+            "radcontrol.dev_strict",
+            VerbSource::Builtin,
+            None,
+            VerbMeta::describe("Restart RadControl + Refresh Status", "radcontrol").refreshing_ports(),
+        ),
+        VerbInfo::new(
+            "port_status",
+            VerbSource::Builtin,
+            None,
+            VerbMeta::describe("Probe a port", "ports"),
+        )
+        .with_params(port()),
+        VerbInfo::new(
+            "kill_port",
+            VerbSource::Builtin,
+            None,
+            VerbMeta::describe("Stop whatever listens on a port", "ports")
+                .destructive()
+                .refreshing_ports(),
+        )
+        .with_params(port()),
+    ]
+}
+
+/// Every verb run_o2 may execute: catalog first, then registry hooks and
+/// per-port probes, then built-ins. A verb listed by several sources keeps
+/// its first source; later ones only fill in what it leaves unset (project,
+/// params, metadata), so the catalog can annotate registry hooks. A broken
+/// catalog entry or hook is reported in `errors` and skipped; the built-ins
+/// are always there.
+pub fn load_verbs() -> VerbLoad {
+    let mut errors = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut verbs: Vec<VerbInfo> = Vec::new();
+
+    let sources = [catalog_verbs(&mut errors), registry_verbs(&mut errors), builtin_verbs()];
+    for info in sources.into_iter().flatten() {
+        if info.verb.is_empty() {
+            continue;
+        }
+        match index.get(&info.verb) {
+            Some(&i) => {
+                let existing = &mut verbs[i];
+                existing.project = existing.project.take().or(info.project);
+                if existing.params.is_empty() {
+                    existing.params = info.params;
+                }
+                existing.meta.fill_from(info.meta);
+            }
+            None => {
+                index.insert(info.verb.clone(), verbs.len());
+                verbs.push(info);
+            }
+        }
+    }
+
+    VerbLoad { verbs, errors }
+}
+
+pub fn allowed_verbs() -> Vec<VerbInfo> {
+    load_verbs().verbs
+}
+
+#[tauri::command]
+pub fn o2_list_verbs() -> VerbLoad {
+    load_verbs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn params() -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::new("port", ParamType::Port, true, None),
+            ParamSpec::new("project", ParamType::ProjectKey, false, None),
+            ParamSpec::new("message", ParamType::Text, false, Some(5)),
+        ]
+    }
+
+    fn check(args: Value) -> Result<O2Args, String> {
+        let args = args.as_object().unwrap().clone();
+        check_args_with(&params(), &args, &|key| Ok(key == "tbis"))
+    }
+
+    #[test]
+    fn ports_are_range_checked() {
+        assert_eq!(check(json!({"port": 1})).unwrap()["port"], "1");
+        assert_eq!(check(json!({"port": " 65535 "})).unwrap()["port"], "65535");
+        for bad in [json!(0), json!(65536), json!(-1), json!("80a"), json!(3000.5), json!(true)] {
+            assert!(check(json!({ "port": bad })).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn text_rejects_control_characters_and_overlong_values() {
+        let ok = check(json!({"port": 80, "message": "a\tb\r\n"})).unwrap();
+        assert_eq!(ok["message"], "a\tb\r\n");
+        assert!(check(json!({"port": 80, "message": "héllo"})).is_ok());
+        assert!(check(json!({"port": 80, "message": "toolong"})).is_err());
+        for bad in ["a\0", "\u{1b}[2J", "a\u{7f}", "\u{85}"] {
+            assert!(check(json!({"port": 80, "message": bad})).is_err(), "{bad:?}");
+        }
+        assert!(check(json!({"port": 80, "message": 5})).is_err());
+    }
+
+    #[test]
+    fn project_keys_must_exist() {
+        assert_eq!(check(json!({"port": 80, "project": " tbis "})).unwrap()["project"], "tbis");
+        assert!(check(json!({"port": 80, "project": "dqotd"})).is_err());
+        assert!(check(json!({"port": 80, "project": ""})).is_err());
+    }
+
+    #[test]
+    fn missing_extra_and_null_args() {
+        assert_eq!(check(json!({})).unwrap_err(), "missing argument: port");
+        assert_eq!(check(json!({"port": null})).unwrap_err(), "missing argument: port");
+        assert_eq!(check(json!({"port": 80, "extra": 1})).unwrap_err(), "unknown argument: extra");
+        let args = check(json!({"port": 80, "project": null})).unwrap();
+        assert!(!args.contains_key("project"));
+    }
+}
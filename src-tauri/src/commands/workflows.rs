@@ -0,0 +1,495 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+
+use super::health::{check_project, HEALTH_TIMEOUT};
+use super::history::HistoryStore;
+use super::jobs::{now_ms, JobTable};
+use super::logs::LogStore;
+use super::o2::{check_verb, run_o2_recorded, O2Call, O2OutputEvent, O2_OUTPUT_EVENT};
+use super::registry::{load_registry, o2_root, Project};
+use crate::git::repo_status;
+use crate::repo_index::{load_profile, write_index};
+use crate::snapshot::write_snapshot;
+
+pub const WORKFLOW_STEP_EVENT: &str = "workflow-step";
+pub const WORKFLOW_FINISHED_EVENT: &str = "workflow-finished";
+
+// Native steps without their own timeoutMs.
+const DEFAULT_NATIVE_STEP_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// What a step does. Native steps act on the workflow's project.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum StepAction {
+    /// Run an allowlisted, non-destructive O2 verb.
+    Verb {
+        verb: String,
+        #[serde(default)]
+        args: Map<String, Value>,
+    },
+    Snapshot,
+    Index,
+    Health,
+    GitStatus,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StepDef {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub action: StepAction,
+    /// Overrides the workflow's `continueOnError` for this step.
+    #[serde(default)]
+    pub continue_on_error: Option<bool>,
+    /// Verb steps default to the verb's timeout (see `timeouts.rs`).
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+impl StepDef {
+    fn label(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        match &self.action {
+            StepAction::Verb { verb, .. } => verb.clone(),
+            StepAction::Snapshot => "snapshot".to_string(),
+            StepAction::Index => "index".to_string(),
+            StepAction::Health => "health".to_string(),
+            StepAction::GitStatus => "git status".to_string(),
+        }
+    }
+}
+
+/// One entry of `$O2_ROOT/registry/workflows.json`. With `project` set the
+/// workflow only applies to that project; without it, it runs against
+/// whichever project it is started for.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowDef {
+    pub id: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub continue_on_error: bool,
+    pub steps: Vec<StepDef>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Running,
+    Succeeded,
+    Failed,
+    TimedOut,
+    /// Not run because an earlier step failed.
+    Skipped,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StepReport {
+    pub index: usize,
+    pub name: String,
+    pub status: StepStatus,
+    pub started_at_ms: Option<u64>,
+    pub ended_at_ms: Option<u64>,
+    /// O2 job id for verb steps.
+    pub job_id: Option<String>,
+    /// Step-specific result (snapshot summary, git status, ...).
+    pub detail: Option<Value>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowReport {
+    pub run_id: String,
+    pub workflow: String,
+    pub project: String,
+    pub started_at_ms: u64,
+    pub ended_at_ms: u64,
+    pub ok: bool,
+    pub steps: Vec<StepReport>,
+}
+
+/// Emitted as `workflow-step` when a step starts and again when it ends.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowStepEvent {
+    pub run_id: String,
+    pub workflow: String,
+    pub total: usize,
+    pub step: StepReport,
+}
+
+/// The built-in equivalent of scripts/o2_session_start.sh.
+fn builtin_workflows() -> Vec<WorkflowDef> {
+    let step = |action| StepDef {
+        name: None,
+        action,
+        continue_on_error: None,
+        timeout_ms: None,
+    };
+    vec![WorkflowDef {
+        id: "session_start".to_string(),
+        description: Some("git status, repo index, snapshot".to_string()),
+        project: None,
+        continue_on_error: true,
+        steps: vec![
+            step(StepAction::GitStatus),
+            step(StepAction::Index),
+            step(StepAction::Snapshot),
+        ],
+    }]
+}
+
+fn catalog_workflows() -> Result<Vec<WorkflowDef>, String> {
+    let root = o2_root()?;
+    let path = format!("{root}/registry/workflows.json");
+
+    if !std::path::Path::new(&path).is_file() {
+        return Ok(Vec::new());
+    }
+
+    let s = fs::read_to_string(&path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    serde_json::from_str(&s).map_err(|e| format!("Invalid workflows file {path}: {e}"))
+}
+
+/// Workflows usable for `project` (all of them when None). A project-scoped
+/// workflow shadows a global one with the same id; the catalog shadows
+/// built-ins.
+pub fn list_workflows(project: Option<&str>) -> Result<Vec<WorkflowDef>, String> {
+    let mut out: Vec<WorkflowDef> = Vec::new();
+    let mut defs = catalog_workflows()?;
+    defs.extend(builtin_workflows());
+
+    // Scoped definitions first so they win the id.
+    defs.sort_by_key(|w| w.project.is_none());
+    for w in defs {
+        let applies = match (project, &w.project) {
+            (Some(p), Some(scope)) => p == scope,
+            _ => true,
+        };
+        if applies && !out.iter().any(|o| o.id == w.id && (project.is_some() || o.project == w.project)) {
+            out.push(w);
+        }
+    }
+    Ok(out)
+}
+
+/// Check every verb step up front so a workflow never stops halfway over a
+/// typo. Destructive verbs are refused: there's no one to confirm them.
+fn validate(def: &WorkflowDef) -> Result<(), String> {
+    for (i, step) in def.steps.iter().enumerate() {
+        if let StepAction::Verb { verb, args } = &step.action {
+            let (info, _) = check_verb(verb, Some(args)).map_err(|e| format!("step {i}: {}", e.message))?;
+            if info.is_destructive() {
+                return Err(format!("step {i}: destructive verb {} can't run in a workflow", info.verb));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn to_detail<T: Serialize>(v: T) -> Option<Value> {
+    serde_json::to_value(v).ok()
+}
+
+/// Run a native step on a helper thread so its timeout can be enforced.
+/// A step that overruns is reported as timed out and left to finish in the
+/// background; none of them hold locks other steps need.
+fn run_native(action: &StepAction, project: &Project, timeout: Duration) -> (StepStatus, Option<Value>, Option<String>) {
+    let (tx, rx) = mpsc::channel();
+    let action = action.clone();
+    let project = project.clone();
+    thread::spawn(move || {
+        let res: Result<Option<Value>, String> = (|| match action {
+            StepAction::Snapshot => Ok(to_detail(write_snapshot(&project.label, &project.repo_dir()?)?)),
+            StepAction::Index => {
+                let repo = project.repo_dir()?;
+                let (profile, source) = load_profile(&o2_root()?, &project.key, &repo)?;
+                Ok(to_detail(write_index(&project.label, &repo, &profile, &source)?))
+            }
+            StepAction::GitStatus => Ok(to_detail(repo_status(&project.repo_dir()?)?)),
+            StepAction::Health => {
+                let h = check_project(&project, HEALTH_TIMEOUT)
+                    .ok_or_else(|| format!("project {} has no url", project.key))?;
+                if h.ok {
+                    Ok(to_detail(h))
+                } else {
+                    Err(h.err.clone().unwrap_or_else(|| format!("unhealthy (status {:?})", h.status)))
+                }
+            }
+            StepAction::Verb { .. } => unreachable!("verb steps run through run_o2"),
+        })();
+        let _ = tx.send(res);
+    });
+
+    match rx.recv_timeout(timeout) {
+        Ok(Ok(detail)) => (StepStatus::Succeeded, detail, None),
+        Ok(Err(e)) => (StepStatus::Failed, None, Some(e)),
+        Err(_) => (
+            StepStatus::TimedOut,
+            None,
+            Some(format!("timed out after {}ms", timeout.as_millis())),
+        ),
+    }
+}
+
+/// Run `def` against `project`, calling `on_step` as each step starts and
+/// ends and `on_line` for verb output. Verb steps are recorded in history
+/// with `trigger`.
+#[allow(clippy::too_many_arguments)]
+pub fn run_workflow<S, L>(
+    jobs: &JobTable,
+    history: &HistoryStore,
+    logs: &LogStore,
+    run_id: &str,
+    def: &WorkflowDef,
+    project: &Project,
+    trigger: &'static str,
+    mut on_step: S,
+    mut on_line: L,
+) -> WorkflowReport
+where
+    S: FnMut(&StepReport),
+    L: FnMut(&str, &'static str, &str),
+{
+    let started_at_ms = now_ms();
+    let mut steps = Vec::new();
+    let mut failed = false;
+
+    for (index, step) in def.steps.iter().enumerate() {
+        let mut report = StepReport {
+            index,
+            name: step.label(),
+            status: StepStatus::Skipped,
+            started_at_ms: None,
+            ended_at_ms: None,
+            job_id: None,
+            detail: None,
+            error: None,
+        };
+        if failed {
+            on_step(&report);
+            steps.push(report);
+            continue;
+        }
+
+        report.status = StepStatus::Running;
+        report.started_at_ms = Some(now_ms());
+        let timeout = step.timeout_ms.map(Duration::from_millis);
+
+        match &step.action {
+            StepAction::Verb { verb, args } => match check_verb(verb, Some(args)) {
+                Ok((info, args)) => {
+                    let mut call = O2Call::new(info.verb, args, trigger);
+                    call.timeout = timeout;
+                    report.job_id = Some(call.job_id.clone());
+                    on_step(&report);
+
+                    let started = Instant::now();
+                    let r = run_o2_recorded(jobs, history, logs, &call, |stream, line| on_line(&call.job_id, stream, line));
+                    report.status = if r.timed_out {
+                        StepStatus::TimedOut
+                    } else if r.ok {
+                        StepStatus::Succeeded
+                    } else {
+                        StepStatus::Failed
+                    };
+                    if !r.ok {
+                        report.error = Some(if r.cancelled {
+                            "cancelled".to_string()
+                        } else {
+                            format!("exit code {}", r.code)
+                        });
+                    }
+                    report.detail = to_detail(serde_json::json!({
+                        "code": r.code,
+                        "elapsedMs": started.elapsed().as_millis() as u64,
+                    }));
+                }
+                // The registry or catalog changed since validation.
+                Err(e) => {
+                    on_step(&report);
+                    report.status = StepStatus::Failed;
+                    report.error = Some(e.message);
+                }
+            },
+            action => {
+                on_step(&report);
+                let (status, detail, error) =
+                    run_native(action, project, timeout.unwrap_or(DEFAULT_NATIVE_STEP_TIMEOUT));
+                report.status = status;
+                report.detail = detail;
+                report.error = error;
+            }
+        }
+
+        report.ended_at_ms = Some(now_ms());
+        if report.status != StepStatus::Succeeded && !step.continue_on_error.unwrap_or(def.continue_on_error) {
+            failed = true;
+        }
+        on_step(&report);
+        steps.push(report);
+    }
+
+    WorkflowReport {
+        run_id: run_id.to_string(),
+        workflow: def.id.clone(),
+        project: project.key.clone(),
+        started_at_ms,
+        ended_at_ms: now_ms(),
+        ok: steps.iter().all(|s| s.status == StepStatus::Succeeded),
+        steps,
+    }
+}
+
+/// Look up a workflow and its project and validate the pair.
+pub fn resolve_workflow(id: &str, project_key: &str) -> Result<(WorkflowDef, Project), String> {
+    let project = load_registry()?
+        .projects
+        .into_iter()
+        .find(|p| p.key == project_key)
+        .ok_or_else(|| format!("unknown project: {project_key}"))?;
+    let def = list_workflows(Some(&project.key))?
+        .into_iter()
+        .find(|w| w.id == id)
+        .ok_or_else(|| format!("no workflow {id} for project {project_key}"))?;
+    validate(&def)?;
+    Ok((def, project))
+}
+
+pub fn next_run_id() -> String {
+    static SEQ: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+    format!(
+        "wf-{}-{}",
+        now_ms(),
+        SEQ.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+    )
+}
+
+#[tauri::command]
+pub fn o2_list_workflows(project: Option<String>) -> Result<Vec<WorkflowDef>, String> {
+    list_workflows(project.as_deref().map(str::trim))
+}
+
+/// Start a workflow for a project. Returns the run id right away; progress
+/// arrives as `workflow-step` events (plus `o2-output` for verb steps) and
+/// the report as `workflow-finished`.
+#[tauri::command]
+pub fn o2_run_workflow(
+    app: AppHandle,
+    jobs: State<'_, JobTable>,
+    history: State<'_, HistoryStore>,
+    logs: State<'_, LogStore>,
+    id: String,
+    project: String,
+) -> Result<String, String> {
+    let (def, project) = resolve_workflow(id.trim(), project.trim())?;
+
+    let run_id = next_run_id();
+    let rid = run_id.clone();
+    let jobs = jobs.inner().clone();
+    let history = history.inner().clone();
+    let logs = logs.inner().clone();
+    thread::spawn(move || {
+        let total = def.steps.len();
+        let report = run_workflow(
+            &jobs,
+            &history,
+            &logs,
+            &rid,
+            &def,
+            &project,
+            "workflow",
+            |step| {
+                let _ = app.emit(
+                    WORKFLOW_STEP_EVENT,
+                    WorkflowStepEvent {
+                        run_id: rid.clone(),
+                        workflow: def.id.clone(),
+                        total,
+                        step: step.clone(),
+                    },
+                );
+            },
+            |job_id, stream, line| {
+                let _ = app.emit(
+                    O2_OUTPUT_EVENT,
+                    O2OutputEvent {
+                        job_id: job_id.to_string(),
+                        stream,
+                        line: line.to_string(),
+                    },
+                );
+            },
+        );
+        let _ = app.emit(WORKFLOW_FINISHED_EVENT, report);
+    });
+
+    Ok(run_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn workflow(steps: Value) -> Result<WorkflowDef, serde_json::Error> {
+        serde_json::from_value(json!({ "id": "test", "steps": steps }))
+    }
+
+    #[test]
+    fn validate_refuses_destructive_and_unknown_verbs() {
+        let ok = workflow(json!([{ "kind": "verb", "verb": "port_status", "args": { "port": 3000 } }])).unwrap();
+        assert!(validate(&ok).is_ok());
+
+        let destructive = workflow(json!([
+            { "kind": "gitStatus" },
+            { "kind": "verb", "verb": "kill_port", "args": { "port": 3000 } },
+        ]))
+        .unwrap();
+        let err = validate(&destructive).unwrap_err();
+        assert!(err.starts_with("step 1:") && err.contains("destructive"), "{err}");
+
+        let unknown = workflow(json!([{ "kind": "verb", "verb": "no.such.verb" }])).unwrap();
+        assert!(validate(&unknown).unwrap_err().starts_with("step 0:"));
+
+        let bad_args = workflow(json!([{ "kind": "verb", "verb": "port_status", "args": { "port": 0 } }])).unwrap();
+        assert!(validate(&bad_args).is_err());
+    }
+
+    #[test]
+    fn unknown_step_kinds_do_not_parse() {
+        assert!(workflow(json!([{ "kind": "shell", "command": "rm -rf /" }])).is_err());
+        assert!(workflow(json!([{ "verb": "port_status" }])).is_err());
+        let steps = workflow(json!([{ "kind": "snapshot" }, { "kind": "index" }, { "kind": "health" }])).unwrap().steps;
+        assert_eq!(steps.iter().map(StepDef::label).collect::<Vec<_>>(), ["snapshot", "index", "health"]);
+    }
+
+    #[test]
+    fn builtin_session_start_loads_and_validates() {
+        // Straight from the built-ins: a local catalog may shadow the id.
+        let defs = builtin_workflows();
+        let def = defs.iter().find(|w| w.id == "session_start").unwrap();
+        assert!(validate(def).is_ok());
+        assert!(def.continue_on_error);
+        let labels: Vec<String> = def.steps.iter().map(StepDef::label).collect();
+        assert_eq!(labels, ["git status", "index", "snapshot"]);
+
+        // The catalog form of the same workflow parses back identically.
+        let round_trip: WorkflowDef = serde_json::from_value(serde_json::to_value(def).unwrap()).unwrap();
+        assert_eq!(round_trip.steps.len(), def.steps.len());
+    }
+}
@@ -0,0 +1,405 @@
+// Opt-in control socket so shell scripts and editor tasks can drive the
+// running app through the same job table, confirmation tokens and history
+// as the UI, instead of calling run_o2.sh behind its back.
+//
+// Enabled by RADCONTROL_CONTROL_SOCKET: a socket path, or "1" for the
+// default ($XDG_RUNTIME_DIR/radcontrol.sock, else <app data>/control.sock).
+// The socket is mode 0600, its directory must belong to us and not be group-
+// or world-writable, and peers running as another user are refused.
+//
+// Protocol: one JSON request per line,
+//   {"id": 1, "method": "run", "params": {"verb": "...", "stream": true}}
+// answered by one response line,
+//   {"id": 1, "ok": true, "result": ...} or {"id": 1, "ok": false, "error": "..."}
+// A streaming `run` first sends {"id": 1, "event": "output", "jobId", "stream", "line"}
+// per line of verb output. `jobs.follow` sends the same events for any job,
+// including one started by the UI or another connection: what the log ring
+// still holds, then new lines until the job ends. Requests on one connection
+// are handled in order.
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::commands::confirm::{prepare_verb, ConfirmTokens};
+use crate::commands::history::{HistoryFilter, HistoryStore};
+use crate::commands::jobs::{JobState, JobTable};
+use crate::commands::logs::LogStore;
+use crate::commands::o2::{check_verb, log_source_for_verb, run_o2_recorded, O2Call};
+use crate::commands::ports::port_status;
+use crate::commands::registry::load_registry;
+use crate::commands::verbs::load_verbs;
+
+pub const SOCKET_ENV: &str = "RADCONTROL_CONTROL_SOCKET";
+
+// Longest request line accepted; verb args are small.
+const MAX_REQUEST_BYTES: u64 = 1024 * 1024;
+// How often jobs.follow checks the log ring for new lines.
+const FOLLOW_POLL: Duration = Duration::from_millis(200);
+
+#[derive(Clone)]
+pub struct ControlState {
+    pub jobs: JobTable,
+    pub history: HistoryStore,
+    pub logs: LogStore,
+    pub tokens: ConfirmTokens,
+}
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Map<String, Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RunParams {
+    verb: String,
+    #[serde(default)]
+    args: Option<Map<String, Value>>,
+    #[serde(default)]
+    confirm_token: Option<String>,
+    #[serde(default)]
+    confirm_phrase: Option<String>,
+    /// Send output lines as events before the result.
+    #[serde(default)]
+    stream: bool,
+}
+
+#[derive(Deserialize)]
+struct PrepareParams {
+    verb: String,
+    #[serde(default)]
+    args: Option<Map<String, Value>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JobParams {
+    job_id: String,
+}
+
+#[derive(Deserialize)]
+struct IdParams {
+    id: String,
+}
+
+#[derive(Deserialize, Default)]
+struct PortParams {
+    /// Defaults to every registry project with a port.
+    #[serde(default)]
+    ports: Option<Vec<u16>>,
+}
+
+/// Socket path from RADCONTROL_CONTROL_SOCKET, or None when the API is off.
+pub fn socket_path(data_dir: &Path) -> Option<PathBuf> {
+    let v = std::env::var(SOCKET_ENV).ok()?;
+    match v.trim() {
+        "" | "0" => None,
+        "1" => Some(match std::env::var_os("XDG_RUNTIME_DIR").filter(|d| !d.is_empty()) {
+            Some(dir) => PathBuf::from(dir).join("radcontrol.sock"),
+            None => data_dir.join("control.sock"),
+        }),
+        p => Some(PathBuf::from(p)),
+    }
+}
+
+fn params<T: DeserializeOwned>(p: Map<String, Value>) -> Result<T, String> {
+    serde_json::from_value(Value::Object(p)).map_err(|e| format!("invalid params: {e}"))
+}
+
+fn to_value<T: serde::Serialize>(v: T) -> Result<Value, String> {
+    serde_json::to_value(v).map_err(|e| format!("Failed to serialize result: {e}"))
+}
+
+fn send(out: &mut UnixStream, v: &Value) -> std::io::Result<()> {
+    let mut line = v.to_string();
+    line.push('\n');
+    out.write_all(line.as_bytes())
+}
+
+fn output_event(id: &Value, job_id: &str, stream: &str, line: &str) -> Value {
+    json!({
+        "id": id,
+        "event": "output",
+        "jobId": job_id,
+        "stream": stream,
+        "line": line,
+    })
+}
+
+/// Send a job's output as events, from what its log ring still holds, until
+/// the job ends; then return the job's final state.
+fn follow_job(state: &ControlState, id: &Value, job_id: &str, out: &mut UnixStream) -> Result<Value, String> {
+    let unknown = || format!("unknown job: {job_id}");
+    let source = log_source_for_verb(&state.jobs.get(job_id).ok_or_else(unknown)?.verb);
+    let mut seen = None;
+    loop {
+        // State first: a job's lines are all logged before it is marked
+        // ended, so the read below can't miss its last ones.
+        let job = state.jobs.get(job_id).ok_or_else(unknown)?;
+        for l in state.logs.since(&source, seen) {
+            seen = Some(l.seq);
+            if l.job_id.as_deref() == Some(job_id) {
+                send(out, &output_event(id, job_id, &l.stream, &l.line))
+                    .map_err(|e| format!("Failed to send output: {e}"))?;
+            }
+        }
+        if job.state != JobState::Running {
+            return to_value(job);
+        }
+        thread::sleep(FOLLOW_POLL);
+    }
+}
+
+fn handle(state: &ControlState, req: Request, out: &mut UnixStream) -> Result<Value, String> {
+    match req.method.as_str() {
+        "projects.list" => to_value(load_registry()?),
+        "ports.status" => {
+            let p: PortParams = params(req.params)?;
+            match p.ports {
+                Some(ports) => to_value(port_status(&ports)),
+                None => {
+                    let projects: Vec<_> = load_registry()?.projects.into_iter().filter(|p| p.port.is_some()).collect();
+                    let ports: Vec<u16> = projects.iter().filter_map(|p| p.port).collect();
+                    let rows: Vec<Value> = projects
+                        .iter()
+                        .zip(port_status(&ports))
+                        .map(|(p, s)| json!({ "key": p.key, "status": s }))
+                        .collect();
+                    Ok(Value::Array(rows))
+                }
+            }
+        }
+        "verbs.list" => to_value(load_verbs()),
+        "verbs.prepare" => {
+            let p: PrepareParams = params(req.params)?;
+            to_value(prepare_verb(&state.tokens, &p.verb, p.args.as_ref()).map_err(|e| e.message)?)
+        }
+        "run" => {
+            let p: RunParams = params(req.params)?;
+            let (info, args) = check_verb(&p.verb, p.args.as_ref()).map_err(|e| e.message)?;
+            state
+                .tokens
+                .check(&info, &args, p.confirm_token.as_deref(), p.confirm_phrase.as_deref())
+                .map_err(|e| e.message)?;
+            let call = O2Call::new(info.verb, args, "api");
+
+            // A client that hangs up mid-run doesn't stop the job.
+            let mut write_ok = true;
+            let result = run_o2_recorded(&state.jobs, &state.history, &state.logs, &call, |stream, line| {
+                if p.stream && write_ok {
+                    write_ok = send(out, &output_event(&req.id, &call.job_id, stream, line)).is_ok();
+                }
+            });
+            Ok(json!({ "jobId": call.job_id, "verb": call.verb, "result": result }))
+        }
+        "jobs.list" => to_value(state.jobs.list()),
+        "jobs.follow" => {
+            let p: JobParams = params(req.params)?;
+            follow_job(state, &req.id, &p.job_id, out)
+        }
+        "jobs.cancel" => {
+            let p: JobParams = params(req.params)?;
+            to_value(state.jobs.cancel(&p.job_id)?)
+        }
+        "history.list" => {
+            let filter: HistoryFilter = params(req.params)?;
+            to_value(state.history.list(&filter)?)
+        }
+        "history.get" => {
+            let p: IdParams = params(req.params)?;
+            let record = state.history.get(&p.id)?.ok_or_else(|| format!("no history record {}", p.id))?;
+            to_value(record)
+        }
+        other => Err(format!("unknown method: {other}")),
+    }
+}
+
+fn peer_uid(stream: &UnixStream) -> Option<u32> {
+    let mut cred = libc::ucred { pid: 0, uid: 0, gid: 0 };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    // Safety: getsockopt writes at most `len` bytes into `cred`.
+    let rc = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    (rc == 0).then_some(cred.uid)
+}
+
+fn serve(state: ControlState, stream: UnixStream) {
+    // Safety: geteuid has no failure mode.
+    let me = unsafe { libc::geteuid() };
+    if peer_uid(&stream) != Some(me) {
+        return;
+    }
+    let Ok(mut out) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(stream);
+
+    loop {
+        let mut line = String::new();
+        match (&mut reader).take(MAX_REQUEST_BYTES).read_line(&mut line) {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {}
+        }
+        if !line.ends_with('\n') && line.len() as u64 >= MAX_REQUEST_BYTES {
+            let _ = send(&mut out, &json!({ "id": null, "ok": false, "error": "request too large" }));
+            return;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = match serde_json::from_str::<Request>(&line) {
+            Ok(req) => {
+                let id = req.id.clone();
+                match handle(&state, req, &mut out) {
+                    Ok(result) => json!({ "id": id, "ok": true, "result": result }),
+                    Err(e) => json!({ "id": id, "ok": false, "error": e }),
+                }
+            }
+            Err(e) => json!({ "id": null, "ok": false, "error": format!("invalid request: {e}") }),
+        };
+        if send(&mut out, &reply).is_err() {
+            return;
+        }
+    }
+}
+
+/// Refuse a socket directory someone else could swap the socket out of:
+/// it must be ours and not writable by group or others (like /tmp).
+fn check_socket_dir(dir: &Path) -> Result<(), String> {
+    let meta = fs::metadata(dir).map_err(|e| format!("Failed to inspect {}: {e}", dir.display()))?;
+    // Safety: geteuid has no failure mode.
+    let me = unsafe { libc::geteuid() };
+    if meta.uid() != me {
+        return Err(format!("{} is owned by uid {}, not {me}", dir.display(), meta.uid()));
+    }
+    if meta.mode() & 0o022 != 0 {
+        return Err(format!(
+            "{} is writable by group or others (mode {:o})",
+            dir.display(),
+            meta.mode() & 0o777
+        ));
+    }
+    Ok(())
+}
+
+/// Bind the socket, replacing a stale one left by a crashed instance but
+/// never one another instance is still serving.
+fn bind(path: &Path) -> Result<UnixListener, String> {
+    let dir = match path.parent() {
+        Some(d) if !d.as_os_str().is_empty() => d,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    check_socket_dir(dir)?;
+
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(format!("{} is already in use", path.display()));
+        }
+        fs::remove_file(path).map_err(|e| format!("Failed to remove stale {}: {e}", path.display()))?;
+    }
+
+    let listener = UnixListener::bind(path).map_err(|e| format!("Failed to bind {}: {e}", path.display()))?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to restrict {}: {e}", path.display()))?;
+    Ok(listener)
+}
+
+/// Start serving on `path`, one thread per connection.
+pub fn spawn(path: PathBuf, state: ControlState) -> Result<(), String> {
+    let listener = bind(&path)?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let state = state.clone();
+            thread::spawn(move || serve(state, stream));
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn socket_dir_must_be_private() {
+        let dir = std::env::temp_dir().join(format!("radcontrol-control-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("control.sock");
+
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o777)).unwrap();
+        let open = bind(&path).map(|_| ());
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700)).unwrap();
+        let private = bind(&path).map(|_| ());
+        let mode = fs::metadata(&path).map(|m| m.mode() & 0o777);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(open.unwrap_err().contains("writable by group or others"));
+        assert!(private.is_ok());
+        assert_eq!(mode.unwrap(), 0o600);
+    }
+
+    #[test]
+    fn follow_replays_the_ring_then_streams_until_the_job_ends() {
+        let dir = std::env::temp_dir().join(format!("radcontrol-follow-{}", std::process::id()));
+        let state = ControlState {
+            jobs: JobTable::default(),
+            history: HistoryStore::new(&dir),
+            logs: LogStore::headless(&dir),
+            tokens: ConfirmTokens::default(),
+        };
+        let (job, other) = ("o2-follow-1", "o2-follow-2");
+        // Not a catalog verb, so its output goes to the "o2" source.
+        state.jobs.register(job, "follow.test");
+        state.logs.append("o2", "stdout", "before", Some(job));
+        state.logs.append("o2", "stdout", "someone else", Some(other));
+
+        let writer = state.clone();
+        let t = thread::spawn(move || {
+            thread::sleep(FOLLOW_POLL * 2);
+            writer.logs.append("o2", "stderr", "after", Some(job));
+            writer.jobs.finish(job, JobState::Succeeded);
+        });
+
+        let (mut out, client) = UnixStream::pair().unwrap();
+        let result = follow_job(&state, &json!(7), job, &mut out);
+        t.join().unwrap();
+        drop(out);
+        let events: Vec<Value> = BufReader::new(client)
+            .lines()
+            .map(|l| serde_json::from_str(&l.unwrap()).unwrap())
+            .collect();
+        let unknown = follow_job(&state, &json!(8), "o2-missing", &mut UnixStream::pair().unwrap().0);
+        let _ = fs::remove_dir_all(&dir);
+
+        let lines: Vec<(&str, &str)> = events
+            .iter()
+            .map(|e| (e["stream"].as_str().unwrap(), e["line"].as_str().unwrap()))
+            .collect();
+        assert_eq!(lines, [("stdout", "before"), ("stderr", "after")]);
+        assert!(events.iter().all(|e| e["id"] == 7 && e["jobId"] == job));
+        assert_eq!(result.unwrap()["state"], "succeeded");
+        assert_eq!(unknown.unwrap_err(), "unknown job: o2-missing");
+    }
+}
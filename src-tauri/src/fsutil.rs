@@ -0,0 +1,32 @@
+// Small filesystem helpers shared by the registry and generated artifacts.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Write `bytes` to `path` via a temp file in the same directory and a
+/// rename, so readers never observe a half-written file.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| format!("No parent directory for {}", path.display()))?;
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let tmp = dir.join(format!(".{name}.tmp-{}", std::process::id()));
+
+    let res = (|| {
+        let mut f = fs::File::create(&tmp)?;
+        f.write_all(bytes)?;
+        f.sync_all()?;
+        fs::rename(&tmp, path)
+    })();
+
+    res.map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("Failed to write {}: {e}", path.display())
+    })
+}
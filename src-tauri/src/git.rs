@@ -0,0 +1,332 @@
+// Read-only git queries against project checkouts. Everything shells out to
+// the git CLI so behaviour matches what people see in their terminal.
+
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Run git in `repo`; None when git fails or isn't a repo.
+pub fn git(repo: &Path, args: &[&str]) -> Option<String> {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["-c", "color.ui=never"])
+        .args(args)
+        .env("GIT_OPTIONAL_LOCKS", "0")
+        .output()
+        .ok()?;
+    out.status
+        .success()
+        .then(|| String::from_utf8_lossy(&out.stdout).to_string())
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LastCommit {
+    pub hash: String,
+    pub subject: String,
+    pub time_ms: u64,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoGitStatus {
+    /// None when HEAD is detached.
+    pub branch: Option<String>,
+    pub head: Option<String>,
+    pub upstream: Option<String>,
+    pub ahead: u32,
+    pub behind: u32,
+    /// Files with changes in the index.
+    pub staged: usize,
+    /// Tracked files with changes in the worktree.
+    pub unstaged: usize,
+    pub untracked: usize,
+    pub conflicted: usize,
+    pub last_commit: Option<LastCommit>,
+    pub rebase_in_progress: bool,
+    pub merge_in_progress: bool,
+}
+
+impl RepoGitStatus {
+    pub fn is_dirty(&self) -> bool {
+        self.staged + self.unstaged + self.untracked + self.conflicted > 0
+    }
+}
+
+/// Fold `git status --porcelain=v2 --branch` output into counts.
+fn parse_porcelain_v2(out: &str, st: &mut RepoGitStatus) {
+    for line in out.lines() {
+        if let Some(header) = line.strip_prefix("# ") {
+            let (name, value) = header.split_once(' ').unwrap_or((header, ""));
+            match name {
+                "branch.oid" if value != "(initial)" => st.head = Some(value.to_string()),
+                "branch.head" if value != "(detached)" => st.branch = Some(value.to_string()),
+                "branch.upstream" => st.upstream = Some(value.to_string()),
+                "branch.ab" => {
+                    for part in value.split_whitespace() {
+                        if let Some(n) = part.strip_prefix('+') {
+                            st.ahead = n.parse().unwrap_or(0);
+                        } else if let Some(n) = part.strip_prefix('-') {
+                            st.behind = n.parse().unwrap_or(0);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let mut fields = line.splitn(3, ' ');
+        match (fields.next(), fields.next()) {
+            (Some("1" | "2"), Some(xy)) => {
+                let mut xy = xy.chars();
+                if xy.next().is_some_and(|x| x != '.') {
+                    st.staged += 1;
+                }
+                if xy.next().is_some_and(|y| y != '.') {
+                    st.unstaged += 1;
+                }
+            }
+            (Some("u"), _) => st.conflicted += 1,
+            (Some("?"), _) => st.untracked += 1,
+            _ => {}
+        }
+    }
+}
+
+fn git_dir(repo: &Path) -> Option<PathBuf> {
+    let dir = git(repo, &["rev-parse", "--git-dir"])?;
+    let dir = PathBuf::from(dir.trim());
+    Some(if dir.is_absolute() { dir } else { repo.join(dir) })
+}
+
+/// Branch, tracking and worktree state for the checkout at `repo`.
+pub fn repo_status(repo: &Path) -> Result<RepoGitStatus, String> {
+    if !repo.is_dir() {
+        return Err(format!("repo path is not a directory: {}", repo.display()));
+    }
+    let dir = git_dir(repo).ok_or_else(|| format!("not a git repository: {}", repo.display()))?;
+    let porcelain = git(repo, &["status", "--porcelain=v2", "--branch"])
+        .ok_or_else(|| format!("git status failed in {}", repo.display()))?;
+
+    let mut st = RepoGitStatus::default();
+    parse_porcelain_v2(&porcelain, &mut st);
+
+    st.last_commit = git(repo, &["log", "-1", "--format=%H%x00%ct%x00%s"]).and_then(|s| {
+        let mut parts = s.trim_end_matches('\n').splitn(3, '\0');
+        let hash = parts.next()?.to_string();
+        let secs = parts.next()?.parse::<u64>().ok()?;
+        let subject = parts.next().unwrap_or("").to_string();
+        Some(LastCommit {
+            hash,
+            subject,
+            time_ms: secs * 1000,
+        })
+    });
+
+    st.rebase_in_progress = dir.join("rebase-merge").exists() || dir.join("rebase-apply").exists();
+    st.merge_in_progress = dir.join("MERGE_HEAD").exists();
+    Ok(st)
+}
+
+// Unified diff returned for review; the summary counts are always complete.
+pub(crate) const MAX_PREVIEW_DIFF_BYTES: usize = 64 * 1024;
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChange {
+    pub path: String,
+    /// None for binary files.
+    pub insertions: Option<u32>,
+    pub deletions: Option<u32>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffSummary {
+    pub files: Vec<FileChange>,
+    pub insertions: u32,
+    pub deletions: u32,
+}
+
+/// `git diff --numstat` (or `--cached`) folded into per-file and total counts.
+pub fn diff_summary(repo: &Path, cached: bool) -> DiffSummary {
+    let mut args = vec!["diff", "--numstat"];
+    if cached {
+        args.push("--cached");
+    }
+    let out = git(repo, &args).unwrap_or_default();
+
+    let mut summary = DiffSummary::default();
+    for line in out.lines() {
+        let mut parts = line.splitn(3, '\t');
+        let (Some(ins), Some(del), Some(path)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        let insertions = ins.parse::<u32>().ok();
+        let deletions = del.parse::<u32>().ok();
+        summary.insertions += insertions.unwrap_or(0);
+        summary.deletions += deletions.unwrap_or(0);
+        summary.files.push(FileChange {
+            path: path.to_string(),
+            insertions,
+            deletions,
+        });
+    }
+    summary
+}
+
+pub fn untracked_files(repo: &Path) -> Vec<String> {
+    git(repo, &["ls-files", "--others", "--exclude-standard"])
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// At most `cap` bytes of `git <args>` output, and whether there was more.
+/// git is killed at the cap instead of being read to the end.
+fn git_capped(repo: &Path, args: &[&str], cap: usize) -> (Vec<u8>, bool) {
+    let Ok(mut child) = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["-c", "color.ui=never"])
+        .args(args)
+        .env("GIT_OPTIONAL_LOCKS", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return (Vec::new(), false);
+    };
+
+    let mut out = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        let _ = stdout.take(cap as u64 + 1).read_to_end(&mut out);
+    }
+    let more = out.len() > cap;
+    if more {
+        out.truncate(cap);
+        let _ = child.kill();
+    }
+    let _ = child.wait();
+    (out, more)
+}
+
+/// Drop a UTF-8 sequence left incomplete at the end of `buf`.
+fn cut_partial_char(buf: &mut Vec<u8>) {
+    let Some(lead) = buf.iter().rposition(|b| b & 0xC0 != 0x80) else {
+        return;
+    };
+    let need = match buf[lead] {
+        b if b >= 0xF0 => 4,
+        b if b >= 0xE0 => 3,
+        b if b >= 0xC0 => 2,
+        _ => 1,
+    };
+    if buf.len() - lead < need {
+        buf.truncate(lead);
+    }
+}
+
+/// Staged then unstaged unified diff, cut at MAX_PREVIEW_DIFF_BYTES.
+pub fn bounded_diff(repo: &Path) -> (String, bool) {
+    let (mut diff, mut truncated) = git_capped(repo, &["diff", "--cached"], MAX_PREVIEW_DIFF_BYTES);
+    if !truncated {
+        let (unstaged, more) = git_capped(repo, &["diff"], MAX_PREVIEW_DIFF_BYTES - diff.len());
+        diff.extend(unstaged);
+        truncated = more;
+    }
+    if truncated {
+        cut_partial_char(&mut diff);
+    }
+    (String::from_utf8_lossy(&diff).to_string(), truncated)
+}
+
+/// Fingerprint of HEAD, the index, the worktree diff and the untracked file
+/// list. Two equal tokens mean nothing a commit would pick up has changed.
+pub fn worktree_token(repo: &Path) -> String {
+    let mut h = DefaultHasher::new();
+    for args in [
+        &["rev-parse", "HEAD"][..],
+        &["diff", "--cached", "--binary"][..],
+        &["diff", "--binary"][..],
+        &["ls-files", "--others", "--exclude-standard"][..],
+    ] {
+        git(repo, args).hash(&mut h);
+    }
+    format!("{:016x}", h.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cut_keeps_only_whole_characters() {
+        let cut = |bytes: &[u8]| {
+            let mut v = bytes.to_vec();
+            cut_partial_char(&mut v);
+            v
+        };
+        let euro = "€".as_bytes();
+        assert_eq!(cut(b"abc"), b"abc");
+        assert_eq!(cut(&[b"a", euro].concat()), [b"a", euro].concat());
+        assert_eq!(cut(&[b"a", &euro[..2]].concat()), b"a");
+        assert_eq!(cut(&[b"a", &euro[..1]].concat()), b"a");
+        assert_eq!(cut(&"😀".as_bytes()[..3]), b"");
+    }
+
+    fn parse(out: &str) -> RepoGitStatus {
+        let mut st = RepoGitStatus::default();
+        parse_porcelain_v2(out, &mut st);
+        st
+    }
+
+    #[test]
+    fn porcelain_v2_branch_headers() {
+        let st = parse(concat!(
+            "# branch.oid 1f0c2a9d3e4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d\n",
+            "# branch.head main\n",
+            "# branch.upstream origin/main\n",
+            "# branch.ab +3 -2\n",
+        ));
+        assert_eq!(st.head.as_deref(), Some("1f0c2a9d3e4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d"));
+        assert_eq!(st.branch.as_deref(), Some("main"));
+        assert_eq!(st.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((st.ahead, st.behind), (3, 2));
+        assert!(!st.is_dirty());
+
+        let st = parse("# branch.oid (initial)\n# branch.head (detached)\n");
+        assert_eq!((st.head, st.branch, st.upstream), (None, None, None));
+        assert_eq!((st.ahead, st.behind), (0, 0));
+    }
+
+    #[test]
+    fn porcelain_v2_entries() {
+        let st = parse(concat!(
+            "# branch.head main\n",
+            // ordinary: staged only, unstaged only, both
+            "1 M. N... 100644 100644 100644 aaaa bbbb src/staged.rs\n",
+            "1 .M N... 100644 100644 100644 aaaa aaaa src/unstaged.rs\n",
+            "1 MM N... 100644 100644 100644 aaaa bbbb src/both file.rs\n",
+            // renamed in the index; the original path follows a tab
+            "2 R. N... 100644 100644 100644 aaaa aaaa R100 src/new.rs\tsrc/old.rs\n",
+            // unmerged
+            "u UU N... 100644 100644 100644 100644 aaaa bbbb cccc src/conflict.rs\n",
+            "? notes.txt\n",
+            "? docs/new dir/\n",
+            "! ignored.log\n",
+        ));
+        assert_eq!(st.staged, 3);
+        assert_eq!(st.unstaged, 2);
+        assert_eq!(st.conflicted, 1);
+        assert_eq!(st.untracked, 2);
+        assert!(st.is_dirty());
+    }
+}
@@ -1,5 +1,15 @@
-mod commands;
+pub mod commands;
+mod control_api;
+mod fsutil;
+pub mod git;
+mod process;
+mod procfs;
+mod registry_watch;
+pub mod repo_index;
+mod resource_sampler;
 mod shell;
+pub mod snapshot;
+mod status_poller;
 
 use tauri::Manager;
 use tauri_plugin_single_instance::init as single_instance;
@@ -15,9 +25,80 @@ pub fn run() {
                 let _ = w.set_focus();
             }
         }))
+        .manage(commands::jobs::JobTable::default())
+        .manage(commands::status::StatusBoard::default())
+        .manage(commands::confirm::ConfirmTokens::default())
+        .manage(commands::resources::ResourceBoard::default())
+        .setup(|app| {
+            let data_dir = app.path().app_data_dir()?;
+            app.manage(commands::history::HistoryStore::new(&data_dir));
+
+            let logs = commands::logs::LogStore::new(&data_dir, app.handle().clone());
+            app.manage(logs.clone());
+
+            if let Some(path) = control_api::socket_path(&data_dir) {
+                let state = control_api::ControlState {
+                    jobs: app.state::<commands::jobs::JobTable>().inner().clone(),
+                    history: app.state::<commands::history::HistoryStore>().inner().clone(),
+                    logs: logs.clone(),
+                    tokens: app.state::<commands::confirm::ConfirmTokens>().inner().clone(),
+                };
+                // The app still works without the socket; say why it's missing.
+                if let Err(e) = control_api::spawn(path, state) {
+                    eprintln!("control api disabled: {e}");
+                }
+            }
+
+            let supervisor = commands::supervisor::Supervisor::new(&data_dir, app.handle().clone(), logs);
+            supervisor.reconcile();
+            app.manage(supervisor.clone());
+
+            registry_watch::spawn(app.handle().clone());
+
+            let board = app.state::<commands::status::StatusBoard>().inner().clone();
+            status_poller::spawn(app.handle().clone(), board);
+
+            let resources = app.state::<commands::resources::ResourceBoard>().inner().clone();
+            resource_sampler::spawn(app.handle().clone(), resources, supervisor);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             commands::o2::run_o2,
+            commands::o2::run_o2_stream,
+            commands::confirm::o2_prepare_verb,
+            commands::jobs::o2_list_jobs,
+            commands::jobs::o2_cancel,
+            commands::history::o2_history_list,
+            commands::history::o2_history_get,
+            commands::snapshot::o2_snapshot_project,
+            commands::repo_index::o2_index_project,
+            commands::git::o2_git_status,
+            commands::commit::o2_commit_preview,
+            commands::commit::o2_commit,
+            commands::workflows::o2_list_workflows,
+            commands::workflows::o2_run_workflow,
             commands::registry::o2_list_projects,
+            commands::registry::o2_add_project,
+            commands::registry::o2_update_project,
+            commands::registry::o2_remove_project,
+            commands::verbs::o2_list_verbs,
+            commands::ports::o2_port_status,
+            commands::ports::o2_port_owners,
+            commands::ports::o2_kill_port,
+            commands::health::o2_health_check,
+            commands::panic::o2_panic_stop,
+            commands::status::o2_project_status,
+            commands::status::o2_status_snapshot,
+            commands::status::o2_set_poll_interval,
+            commands::resources::o2_resource_usage,
+            commands::supervisor::o2_supervisor_list,
+            commands::supervisor::o2_supervisor_start,
+            commands::supervisor::o2_supervisor_stop,
+            commands::supervisor::o2_supervisor_restart,
+            commands::logs::o2_logs_sources,
+            commands::logs::o2_logs_tail,
+            commands::logs::o2_logs_follow,
+            commands::logs::o2_logs_search,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
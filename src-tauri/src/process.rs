@@ -0,0 +1,32 @@
+// Process-group signalling for children RadControl spawns.
+//
+// O2 scripts fan out (bash -> npm -> node ...), so signals always target the
+// whole group. Children are started with `process_group(0)`, which makes the
+// child pid the group id.
+
+pub const SIGTERM: i32 = libc::SIGTERM;
+pub const SIGKILL: i32 = libc::SIGKILL;
+
+/// Send `sig` to every process in group `pgid`. Returns false when the group
+/// no longer exists (or we lack permission).
+pub fn signal_group(pgid: u32, sig: i32) -> bool {
+    if pgid == 0 {
+        return false;
+    }
+    // Safety: plain syscall; a negative pid addresses the process group.
+    unsafe { libc::kill(-(pgid as i32), sig) == 0 }
+}
+
+/// Send `sig` to a single process.
+pub fn signal_pid(pid: u32, sig: i32) -> bool {
+    if pid == 0 {
+        return false;
+    }
+    // Safety: plain syscall on a positive pid.
+    unsafe { libc::kill(pid as i32, sig) == 0 }
+}
+
+/// True while any process in group `pgid` is still around (zombies included).
+pub fn group_exists(pgid: u32) -> bool {
+    signal_group(pgid, 0)
+}
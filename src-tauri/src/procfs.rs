@@ -0,0 +1,323 @@
+// Minimal /proc readers: listening TCP sockets and the processes that own
+// them. Linux only, like the rest of RadControl's process handling.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+// `st` column value for LISTEN in /proc/net/tcp{,6}.
+const TCP_LISTEN: &str = "0A";
+
+/// Listening TCP ports (v4 and v6) mapped to their socket inodes, from one
+/// read of /proc/net/tcp and /proc/net/tcp6. Errors only when neither table
+/// is readable.
+pub fn listening_sockets() -> Result<HashMap<u16, Vec<u64>>, String> {
+    let mut out: HashMap<u16, Vec<u64>> = HashMap::new();
+    let mut read_any = false;
+    let mut last_err = String::new();
+
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        let s = match fs::read_to_string(table) {
+            Ok(s) => s,
+            Err(e) => {
+                last_err = format!("Failed to read {table}: {e}");
+                continue;
+            }
+        };
+        read_any = true;
+        parse_listening(&s, &mut out);
+    }
+
+    if read_any {
+        Ok(out)
+    } else {
+        Err(last_err)
+    }
+}
+
+/// Add the LISTEN rows of one /proc/net/tcp{,6} table to `out`.
+fn parse_listening(table: &str, out: &mut HashMap<u16, Vec<u64>>) {
+    for line in table.lines().skip(1) {
+        let cols: Vec<&str> = line.split_whitespace().collect();
+        if cols.len() < 10 || cols[3] != TCP_LISTEN {
+            continue;
+        }
+
+        let port = cols[1]
+            .rsplit_once(':')
+            .and_then(|(_, p)| u16::from_str_radix(p, 16).ok());
+        let inode = cols[9].parse::<u64>().ok();
+
+        if let (Some(port), Some(inode)) = (port, inode) {
+            out.entry(port).or_default().push(inode);
+        }
+    }
+}
+
+/// Numeric entries of /proc.
+pub fn all_pids() -> Vec<u32> {
+    let Ok(rd) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    rd.filter_map(|e| e.ok())
+        .filter_map(|e| e.file_name().to_str().and_then(|n| n.parse::<u32>().ok()))
+        .collect()
+}
+
+/// Walk /proc/*/fd once and map each wanted socket inode to every pid
+/// holding it (forked workers share the listener). Processes we can't
+/// inspect (other users) are skipped.
+pub fn socket_holders(inodes: &HashSet<u64>) -> HashMap<u64, Vec<u32>> {
+    let mut out: HashMap<u64, Vec<u32>> = HashMap::new();
+    if inodes.is_empty() {
+        return out;
+    }
+
+    let mut pids = all_pids();
+    pids.sort_unstable();
+
+    for pid in pids {
+        let Ok(fds) = fs::read_dir(format!("/proc/{pid}/fd")) else {
+            continue;
+        };
+        for fd in fds.filter_map(|e| e.ok()) {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            let inode = target
+                .to_str()
+                .and_then(|t| t.strip_prefix("socket:["))
+                .and_then(|t| t.strip_suffix(']'))
+                .and_then(|t| t.parse::<u64>().ok());
+
+            if let Some(inode) = inode {
+                if inodes.contains(&inode) {
+                    let holders = out.entry(inode).or_default();
+                    if !holders.contains(&pid) {
+                        holders.push(pid);
+                    }
+                }
+            }
+        }
+    }
+
+    out
+}
+
+/// Lowest pid holding each wanted socket inode.
+pub fn socket_owners(inodes: &HashSet<u64>) -> HashMap<u64, u32> {
+    socket_holders(inodes)
+        .into_iter()
+        .filter_map(|(inode, pids)| pids.first().map(|p| (inode, *p)))
+        .collect()
+}
+
+/// Command line with NUL separators turned into spaces; None for kernel
+/// threads or vanished processes.
+pub fn cmdline(pid: u32) -> Option<String> {
+    let raw = fs::read(format!("/proc/{pid}/cmdline")).ok()?;
+    let s = raw
+        .split(|b| *b == 0)
+        .filter(|part| !part.is_empty())
+        .map(|part| String::from_utf8_lossy(part).to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!s.is_empty()).then_some(s)
+}
+
+/// The fields of /proc/<pid>/stat we use.
+#[derive(Clone, Debug)]
+pub struct ProcStat {
+    pub state: char,
+    pub ppid: u32,
+    pub pgrp: u32,
+    /// CPU time in user and kernel mode, in clock ticks.
+    pub utime: u64,
+    pub stime: u64,
+    pub num_threads: u64,
+    /// Clock ticks after boot.
+    pub starttime: u64,
+    /// Resident set size in pages.
+    pub rss_pages: u64,
+}
+
+pub fn stat(pid: u32) -> Option<ProcStat> {
+    parse_stat(&fs::read_to_string(format!("/proc/{pid}/stat")).ok()?)
+}
+
+fn parse_stat(s: &str) -> Option<ProcStat> {
+    // comm may contain spaces and parens; everything after the last ')' is
+    // space-separated, starting at field 3 (state).
+    let rest = &s[s.rfind(')')? + 1..];
+    let f: Vec<&str> = rest.split_whitespace().collect();
+    let num = |i: usize| f.get(i).and_then(|v| v.parse::<u64>().ok());
+
+    Some(ProcStat {
+        state: f.first()?.chars().next()?,
+        ppid: num(1)? as u32,
+        pgrp: num(2)? as u32,
+        utime: num(11)?,
+        stime: num(12)?,
+        num_threads: num(17)?,
+        starttime: num(19)?,
+        rss_pages: num(21)?,
+    })
+}
+
+/// Running (not zombie, not gone).
+pub fn is_alive(pid: u32) -> bool {
+    stat(pid).is_some_and(|s| s.state != 'Z')
+}
+
+pub fn cwd(pid: u32) -> Option<String> {
+    fs::read_link(format!("/proc/{pid}/cwd"))
+        .ok()
+        .map(|p| p.to_string_lossy().to_string())
+}
+
+pub fn clock_ticks_per_sec() -> u64 {
+    // Safety: sysconf has no preconditions.
+    let t = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if t > 0 {
+        t as u64
+    } else {
+        100
+    }
+}
+
+pub fn page_size() -> u64 {
+    // Safety: sysconf has no preconditions.
+    let p = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if p > 0 {
+        p as u64
+    } else {
+        4096
+    }
+}
+
+/// Open file descriptors; None when /proc/<pid>/fd isn't readable to us.
+pub fn fd_count(pid: u32) -> Option<u64> {
+    Some(fs::read_dir(format!("/proc/{pid}/fd")).ok()?.count() as u64)
+}
+
+fn boot_time_secs() -> Option<u64> {
+    let s = fs::read_to_string("/proc/stat").ok()?;
+    s.lines()
+        .find_map(|l| l.strip_prefix("btime "))
+        .and_then(|v| v.trim().parse().ok())
+}
+
+/// Wall-clock start time (epoch ms) of a process from its stat starttime.
+pub fn start_time_ms(starttime: u64) -> Option<u64> {
+    let boot = boot_time_secs()?;
+    Some(boot * 1000 + starttime * 1000 / clock_ticks_per_sec())
+}
+
+/// Parent -> children for every process currently visible.
+pub fn children_map() -> HashMap<u32, Vec<u32>> {
+    let mut out: HashMap<u32, Vec<u32>> = HashMap::new();
+    for pid in all_pids() {
+        if let Some(st) = stat(pid) {
+            out.entry(st.ppid).or_default().push(pid);
+        }
+    }
+    for kids in out.values_mut() {
+        kids.sort_unstable();
+    }
+    out
+}
+
+/// `roots` plus all their descendants, parents before children, no repeats.
+pub fn process_tree(roots: &[u32], children: &HashMap<u32, Vec<u32>>) -> Vec<u32> {
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    let mut queue: std::collections::VecDeque<u32> = roots.iter().copied().collect();
+
+    while let Some(pid) = queue.pop_front() {
+        if !seen.insert(pid) {
+            continue;
+        }
+        out.push(pid);
+        if let Some(kids) = children.get(&pid) {
+            queue.extend(kids.iter().copied());
+        }
+    }
+    out
+}
+
+/// Our own pid and every ancestor up to init.
+pub fn self_and_ancestors() -> HashSet<u32> {
+    let mut out = HashSet::new();
+    let mut pid = std::process::id();
+    while pid > 1 && out.insert(pid) {
+        match stat(pid) {
+            Some(st) => pid = st.ppid,
+            None => break,
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TCP: &str = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0CEA 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41234 1 0000000000000000 100 0 0 10 0
+   1: 00000000:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41235 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:0CEA 0100007F:D2F0 01 00000000:00000000 00:00000000 00000000  1000        0 51000 1 0000000000000000 20 4 30 10 -1
+   3: 0100007F:A1B2 0100007F:0CEA 06 00000000:00000000 03:00000F3B 00000000     0        0 0 3 0000000000000000
+";
+
+    const TCP6: &str = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:1F90 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 61000 1 0000000000000000 100 0 0 10 0
+   1: 00000000000000000000000001000000:1389 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 61001 1 0000000000000000 100 0 0 10 0
+   2: 00000000000000000000000001000000:1389 00000000000000000000000001000000:C350 01 00000000:00000000 00:00000000 00000000  1000        0 61002 1 0000000000000000 20 4 0 10 -1
+";
+
+    #[test]
+    fn listening_rows_from_both_tables() {
+        let mut out = HashMap::new();
+        parse_listening(TCP, &mut out);
+        parse_listening(TCP6, &mut out);
+
+        let mut ports: Vec<u16> = out.keys().copied().collect();
+        ports.sort_unstable();
+        // ESTABLISHED (01) and TIME_WAIT (06) rows are ignored.
+        assert_eq!(ports, [3306, 5001, 8080]);
+        assert_eq!(out[&3306], [41234]);
+        assert_eq!(out[&5001], [61001]);
+        // Dual-stack listeners on one port keep both inodes.
+        assert_eq!(out[&8080], [41235, 61000]);
+    }
+
+    #[test]
+    fn header_only_or_malformed_tables_yield_nothing() {
+        let mut out = HashMap::new();
+        parse_listening(TCP.lines().next().unwrap(), &mut out);
+        parse_listening("header\n   0: garbage 0A\n", &mut out);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn stat_with_spaces_and_parens_in_comm() {
+        let line = "4321 (web (worker) 1) S 4000 4001 4001 0 -1 4194560 1500 0 2 0 250 75 0 0 20 0 7 0 987654 \
+                    123456789 2048 18446744073709551615 1 1 0 0 0 0 0 4096 0 0 0 0 17 3 0 0 0 0 0\n";
+        let st = parse_stat(line).unwrap();
+        assert_eq!(st.state, 'S');
+        assert_eq!(st.ppid, 4000);
+        assert_eq!(st.pgrp, 4001);
+        assert_eq!(st.utime, 250);
+        assert_eq!(st.stime, 75);
+        assert_eq!(st.num_threads, 7);
+        assert_eq!(st.starttime, 987654);
+        assert_eq!(st.rss_pages, 2048);
+    }
+
+    #[test]
+    fn truncated_stat_is_rejected() {
+        assert!(parse_stat("4321 (sh) S 1 4321").is_none());
+        assert!(parse_stat("").is_none());
+    }
+}
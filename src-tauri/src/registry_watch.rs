@@ -0,0 +1,85 @@
+// Polls $O2_ROOT/registry/projects.json and pushes `registry-changed` to the
+// UI whenever it settles after an edit. Polling (rather than inotify) keeps
+// editor save patterns like write-to-temp + rename simple to handle.
+
+use serde::Serialize;
+use std::fs;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter};
+
+use crate::commands::registry::{load_registry, registry_path, RegistryLoad};
+
+pub const REGISTRY_CHANGED_EVENT: &str = "registry-changed";
+
+const POLL: Duration = Duration::from_millis(500);
+// The file must stay unchanged this long before we re-parse it.
+const DEBOUNCE: Duration = Duration::from_millis(400);
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryChanged {
+    pub ok: bool,
+    pub registry: Option<RegistryLoad>,
+    pub error: Option<String>,
+}
+
+#[derive(PartialEq, Eq, Clone)]
+struct Fingerprint {
+    path: String,
+    modified: Option<SystemTime>,
+    len: u64,
+    exists: bool,
+}
+
+fn fingerprint() -> Option<Fingerprint> {
+    let path = registry_path().ok()?;
+    let meta = fs::metadata(&path).ok();
+    Some(Fingerprint {
+        modified: meta.as_ref().and_then(|m| m.modified().ok()),
+        len: meta.as_ref().map(|m| m.len()).unwrap_or(0),
+        exists: meta.is_some(),
+        path,
+    })
+}
+
+fn emit_reload(app: &AppHandle) {
+    let payload = match load_registry() {
+        Ok(reg) => RegistryChanged {
+            ok: true,
+            registry: Some(reg),
+            error: None,
+        },
+        Err(e) => RegistryChanged {
+            ok: false,
+            registry: None,
+            error: Some(e),
+        },
+    };
+    let _ = app.emit(REGISTRY_CHANGED_EVENT, payload);
+}
+
+pub fn spawn(app: AppHandle) {
+    thread::spawn(move || {
+        let mut seen = fingerprint();
+        // (latest fingerprint, when it was first observed)
+        let mut pending: Option<(Option<Fingerprint>, Instant)> = None;
+
+        loop {
+            thread::sleep(POLL);
+            let now = fingerprint();
+
+            match &pending {
+                Some((fp, since)) if *fp == now && since.elapsed() >= DEBOUNCE => {
+                    seen = now;
+                    pending = None;
+                    emit_reload(&app);
+                }
+                Some((fp, _)) if *fp == now => {}
+                Some(_) => pending = Some((now, Instant::now())),
+                None if now != seen => pending = Some((now, Instant::now())),
+                None => {}
+            }
+        }
+    });
+}
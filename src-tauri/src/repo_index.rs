@@ -0,0 +1,634 @@
+// Native equivalent of scripts/o2_index_repo.sh: a bounded repo index with
+// file previews, listings and hotspot greps, driven by an index profile
+// instead of being hard-coded per repo. One walker and one regex engine, so
+// results don't depend on whether ripgrep is installed.
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use crate::fsutil::write_atomic;
+use crate::git::git;
+
+pub const INDEX_TEXT_REL_PATH: &str = "docs/_o2_repo_index.txt";
+pub const INDEX_JSON_REL_PATH: &str = "docs/_o2_repo_index.json";
+
+// Files larger than this, or with a NUL in the first block, are not grepped.
+const MAX_GREP_FILE_BYTES: u64 = 1024 * 1024;
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+const MAX_MATCH_LINE_CHARS: usize = 240;
+
+const RULE: &str = "================================================================================";
+
+fn default_preview_lines() -> usize {
+    200
+}
+
+fn default_listing_depth() -> usize {
+    3
+}
+
+fn default_listing_entries() -> usize {
+    400
+}
+
+fn default_hotspot_path() -> String {
+    ".".to_string()
+}
+
+fn default_hotspot_matches() -> usize {
+    250
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewSpec {
+    pub path: String,
+    #[serde(default = "default_preview_lines")]
+    pub max_lines: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListingSpec {
+    pub path: String,
+    /// Like `find -maxdepth`: 1 lists only files directly under `path`.
+    #[serde(default = "default_listing_depth")]
+    pub max_depth: usize,
+    #[serde(default = "default_listing_entries")]
+    pub max_entries: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HotspotSpec {
+    pub title: String,
+    pub pattern: String,
+    #[serde(default = "default_hotspot_path")]
+    pub path: String,
+    #[serde(default = "default_hotspot_matches")]
+    pub max_matches: usize,
+}
+
+/// What goes into an index. Loaded from JSON; any field left out keeps the
+/// built-in default, which assumes nothing about the repo's layout. Paths are
+/// relative to the repo root and may not leave it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, rename_all = "camelCase")]
+pub struct IndexProfile {
+    /// Directories listed one level deep (like `ls -la`), excludes ignored.
+    pub directories: Vec<String>,
+    pub previews: Vec<PreviewSpec>,
+    pub listings: Vec<ListingSpec>,
+    pub hotspots: Vec<HotspotSpec>,
+    /// Globs relative to the repo root, e.g. `dist/**`. Applied to listings
+    /// and hotspot greps.
+    pub exclude: Vec<String>,
+}
+
+impl Default for IndexProfile {
+    fn default() -> Self {
+        let preview = |path: &str, max_lines| PreviewSpec {
+            path: path.to_string(),
+            max_lines,
+        };
+        let hotspot = |title: &str, pattern: &str| HotspotSpec {
+            title: title.to_string(),
+            pattern: pattern.to_string(),
+            path: "src".to_string(),
+            max_matches: default_hotspot_matches(),
+        };
+
+        IndexProfile {
+            directories: vec![".".to_string(), "docs".to_string()],
+            previews: vec![
+                preview("README.md", 200),
+                preview("package.json", 200),
+                preview("Cargo.toml", 200),
+            ],
+            listings: vec![ListingSpec {
+                path: ".".to_string(),
+                max_depth: 3,
+                max_entries: 400,
+            }],
+            hotspots: vec![
+                hotspot("TODO / FIXME", r"\b(TODO|FIXME|XXX|HACK)\b"),
+                hotspot("ENTRY POINTS", r"fn main\(|createRoot\(|__name__ == .__main__."),
+            ],
+            exclude: [
+                "**/.git/**",
+                "**/node_modules/**",
+                "**/target/**",
+                "**/dist/**",
+                "**/build/**",
+                "**/.next/**",
+                INDEX_TEXT_REL_PATH,
+                INDEX_JSON_REL_PATH,
+                crate::snapshot::SNAPSHOT_REL_PATH,
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        }
+    }
+}
+
+impl IndexProfile {
+    /// Reject paths that are absolute or climb out with `..`. Symlinks that
+    /// lead outside are caught when the path is used (see `confine`).
+    fn check(&self) -> Result<(), String> {
+        let paths = self
+            .directories
+            .iter()
+            .chain(self.previews.iter().map(|p| &p.path))
+            .chain(self.listings.iter().map(|l| &l.path))
+            .chain(self.hotspots.iter().map(|h| &h.path));
+        for p in paths {
+            let inside = Path::new(p)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+            if !inside {
+                return Err(format!("index profile path must stay inside the repo: {p}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Profile keys become file names under `registry/index_profiles/`.
+fn check_profile_key(key: &str) -> Result<(), String> {
+    if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        Ok(())
+    } else {
+        Err(format!("invalid index profile key {key:?}: use A-Z, a-z, 0-9, _ and -"))
+    }
+}
+
+/// Resolve the profile for a project: `$O2_ROOT/registry/index_profiles/<key>.json`,
+/// then `<repo>/.o2/index_profile.json`, then the built-in default. Returns
+/// the profile and where it came from.
+pub fn load_profile(o2_root: &str, key: &str, repo: &Path) -> Result<(IndexProfile, String), String> {
+    check_profile_key(key)?;
+    let candidates = [
+        Path::new(o2_root).join("registry/index_profiles").join(format!("{key}.json")),
+        repo.join(".o2/index_profile.json"),
+    ];
+
+    for path in candidates {
+        if !path.is_file() {
+            continue;
+        }
+        let s = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        let profile = serde_json::from_str::<IndexProfile>(&s)
+            .map_err(|e| format!("Invalid index profile {}: {e}", path.display()))?;
+        profile
+            .check()
+            .map_err(|e| format!("Invalid index profile {}: {e}", path.display()))?;
+        return Ok((profile, path.display().to_string()));
+    }
+
+    Ok((IndexProfile::default(), "builtin".to_string()))
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DirListing {
+    pub path: String,
+    /// `None` when the directory is missing.
+    pub entries: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FilePreview {
+    pub path: String,
+    pub max_lines: usize,
+    /// `None` when the file is missing.
+    pub lines: Option<Vec<String>>,
+    pub truncated: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FileListing {
+    pub path: String,
+    pub files: Vec<String>,
+    pub truncated: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HotspotMatch {
+    pub path: String,
+    pub line: usize,
+    pub text: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HotspotResult {
+    pub title: String,
+    pub pattern: String,
+    pub path: String,
+    pub matches: Vec<HotspotMatch>,
+    pub truncated: bool,
+}
+
+/// Everything in the index; serialized as `_o2_repo_index.json`.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoIndex {
+    pub name: String,
+    pub repo: String,
+    pub generated_at_ms: u64,
+    pub profile_source: String,
+    pub git_status: Option<String>,
+    pub directories: Vec<DirListing>,
+    pub previews: Vec<FilePreview>,
+    pub listings: Vec<FileListing>,
+    pub hotspots: Vec<HotspotResult>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexSummary {
+    pub name: String,
+    pub repo: String,
+    pub text_path: String,
+    pub json_path: String,
+    pub profile_source: String,
+    pub files_listed: usize,
+    pub hotspot_matches: usize,
+    pub bytes: usize,
+    pub generated_at_ms: u64,
+}
+
+fn build_excludes(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut b = GlobSetBuilder::new();
+    for p in patterns {
+        let glob = GlobBuilder::new(p)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| format!("Invalid exclude glob {p}: {e}"))?;
+        b.add(glob);
+    }
+    b.build().map_err(|e| format!("Invalid exclude globs: {e}"))
+}
+
+/// Relative paths use `/` and no leading `./`, which is what globs match.
+fn rel_path(parent: &str, name: &str) -> String {
+    if parent.is_empty() || parent == "." {
+        name.to_string()
+    } else {
+        format!("{parent}/{name}")
+    }
+}
+
+fn is_excluded(excludes: &GlobSet, rel: &str, is_dir: bool) -> bool {
+    // `dir/**` should prune the directory itself, not just its children.
+    excludes.is_match(rel) || (is_dir && excludes.is_match(format!("{rel}/_")))
+}
+
+/// `root/rel` resolved through symlinks, or `None` if it's missing or lands
+/// outside `root`. `root` must already be canonical.
+fn confine(root: &Path, rel: &str) -> Option<PathBuf> {
+    let path = root.join(rel).canonicalize().ok()?;
+    path.starts_with(root).then_some(path)
+}
+
+/// Files under `rel` (relative to `root`), sorted, depth-limited like
+/// `find -maxdepth`. Symlinks are not followed.
+fn walk_files(root: &Path, rel: &str, depth: usize, excludes: &GlobSet, out: &mut Vec<String>) {
+    if depth == 0 {
+        return;
+    }
+    let Ok(rd) = fs::read_dir(root.join(rel)) else {
+        return;
+    };
+    let mut entries: Vec<_> = rd.filter_map(|e| e.ok()).collect();
+    entries.sort_by_key(|e| e.file_name());
+
+    for e in entries {
+        let name = e.file_name().to_string_lossy().to_string();
+        let child = rel_path(rel, &name);
+        let Ok(ft) = e.file_type() else {
+            continue;
+        };
+        if is_excluded(excludes, &child, ft.is_dir()) {
+            continue;
+        }
+        if ft.is_dir() {
+            walk_files(root, &child, depth - 1, excludes, out);
+        } else if ft.is_file() {
+            out.push(child);
+        }
+    }
+}
+
+fn list_directory(root: &Path, rel: &str) -> DirListing {
+    let entries = confine(root, rel).and_then(|p| fs::read_dir(p).ok()).map(|rd| {
+        let mut entries: Vec<_> = rd.filter_map(|e| e.ok()).collect();
+        entries.sort_by_key(|e| e.file_name());
+        entries
+            .into_iter()
+            .map(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                match e.metadata() {
+                    Ok(m) if m.is_dir() => format!("{:>10}  {name}/", "-"),
+                    Ok(m) => format!("{:>10}  {name}", m.len()),
+                    Err(_) => format!("{:>10}  {name}", "?"),
+                }
+            })
+            .collect()
+    });
+    DirListing {
+        path: rel.to_string(),
+        entries,
+    }
+}
+
+fn preview_file(root: &Path, spec: &PreviewSpec) -> FilePreview {
+    let lines = confine(root, &spec.path).and_then(|p| fs::read(p).ok()).map(|b| {
+        String::from_utf8_lossy(&b)
+            .lines()
+            .take(spec.max_lines + 1)
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    });
+    let truncated = lines.as_ref().is_some_and(|l| l.len() > spec.max_lines);
+    FilePreview {
+        path: spec.path.clone(),
+        max_lines: spec.max_lines,
+        lines: lines.map(|mut l| {
+            l.truncate(spec.max_lines);
+            l
+        }),
+        truncated,
+    }
+}
+
+fn read_text(path: &Path) -> Option<String> {
+    let mut f = fs::File::open(path).ok()?;
+    if f.metadata().ok()?.len() > MAX_GREP_FILE_BYTES {
+        return None;
+    }
+    let mut buf = Vec::new();
+    f.read_to_end(&mut buf).ok()?;
+    if buf[..buf.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&buf).to_string())
+}
+
+fn grep_hotspot(root: &Path, spec: &HotspotSpec, excludes: &GlobSet) -> Result<HotspotResult, String> {
+    let re = Regex::new(&spec.pattern).map_err(|e| format!("Invalid hotspot pattern {}: {e}", spec.pattern))?;
+
+    let base = spec.path.trim_start_matches("./").trim_end_matches('/');
+    let mut files = Vec::new();
+    match confine(root, base) {
+        Some(p) if p.is_file() => files.push(base.to_string()),
+        Some(_) => walk_files(root, base, usize::MAX, excludes, &mut files),
+        None => {}
+    }
+
+    let mut matches = Vec::new();
+    let mut truncated = false;
+    'files: for f in files {
+        let Some(text) = read_text(&root.join(&f)) else {
+            continue;
+        };
+        for (i, line) in text.lines().enumerate() {
+            if !re.is_match(line) {
+                continue;
+            }
+            if matches.len() >= spec.max_matches {
+                truncated = true;
+                break 'files;
+            }
+            matches.push(HotspotMatch {
+                path: f.clone(),
+                line: i + 1,
+                text: line.chars().take(MAX_MATCH_LINE_CHARS).collect(),
+            });
+        }
+    }
+
+    Ok(HotspotResult {
+        title: spec.title.clone(),
+        pattern: spec.pattern.clone(),
+        path: spec.path.clone(),
+        matches,
+        truncated,
+    })
+}
+
+/// Collect the index for `root` according to `profile`.
+pub fn build_index(name: &str, root: &Path, profile: &IndexProfile, profile_source: &str) -> Result<RepoIndex, String> {
+    if !root.is_dir() {
+        return Err(format!("repo path is not a directory: {}", root.display()));
+    }
+    profile.check()?;
+    let repo = root.display().to_string();
+    let root = &root
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {e}", root.display()))?;
+    let excludes = build_excludes(&profile.exclude)?;
+
+    let listings = profile
+        .listings
+        .iter()
+        .map(|spec| {
+            let base = spec.path.trim_start_matches("./").trim_end_matches('/');
+            let mut files = Vec::new();
+            if confine(root, base).is_some() {
+                walk_files(root, base, spec.max_depth, &excludes, &mut files);
+            }
+            let truncated = files.len() > spec.max_entries;
+            files.truncate(spec.max_entries);
+            FileListing {
+                path: spec.path.clone(),
+                files,
+                truncated,
+            }
+        })
+        .collect();
+
+    let hotspots = profile
+        .hotspots
+        .iter()
+        .map(|spec| grep_hotspot(root, spec, &excludes))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(RepoIndex {
+        name: name.to_string(),
+        repo,
+        generated_at_ms: chrono::Local::now().timestamp_millis() as u64,
+        profile_source: profile_source.to_string(),
+        git_status: git(root, &["status", "-sb"]),
+        directories: profile.directories.iter().map(|d| list_directory(root, d)).collect(),
+        previews: profile.previews.iter().map(|p| preview_file(root, p)).collect(),
+        listings,
+        hotspots,
+    })
+}
+
+fn section(out: &mut String, title: &str) {
+    out.push_str(&format!("\n{RULE}\n{title}\n{RULE}\n"));
+}
+
+/// Text form, laid out like the script's output.
+pub fn render_index(index: &RepoIndex) -> String {
+    let generated = chrono::DateTime::from_timestamp_millis(index.generated_at_ms as i64)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S %z").to_string())
+        .unwrap_or_default();
+
+    let mut s = String::new();
+    s.push_str(&format!("O2_REPO_INDEX ({})\n", index.name));
+    s.push_str(&format!("Generated: {generated}\n"));
+    s.push_str(&format!("Repo: {}\n", index.repo));
+    s.push_str(&format!("Profile: {}\n", index.profile_source));
+
+    section(&mut s, "GIT STATUS (for context)");
+    s.push_str(index.git_status.as_deref().unwrap_or("(no git)\n"));
+
+    section(&mut s, "DIRECTORIES (bounded)");
+    for d in &index.directories {
+        s.push_str(&format!("\n----- DIR: {} -----\n", d.path));
+        match &d.entries {
+            Some(entries) => entries.iter().for_each(|e| s.push_str(&format!("{e}\n"))),
+            None => s.push_str("[missing]\n"),
+        }
+    }
+
+    section(&mut s, "KEY FILES (bounded previews)");
+    for p in &index.previews {
+        s.push_str(&format!("\n----- FILE: {} (head -n {}) -----\n", p.path, p.max_lines));
+        match &p.lines {
+            Some(lines) => lines.iter().for_each(|l| s.push_str(&format!("{l}\n"))),
+            None => s.push_str("[missing]\n"),
+        }
+        if p.truncated {
+            s.push_str("[truncated]\n");
+        }
+    }
+
+    section(&mut s, "SOURCE TREE (bounded listings)");
+    for l in &index.listings {
+        s.push('\n');
+        l.files.iter().for_each(|f| s.push_str(&format!("{f}\n")));
+        if l.truncated {
+            s.push_str(&format!("[{}: truncated]\n", l.path));
+        }
+    }
+
+    section(&mut s, "HOTSPOT GREPS");
+    for h in &index.hotspots {
+        section(&mut s, &h.title);
+        for m in &h.matches {
+            s.push_str(&format!("{}:{}:{}\n", m.path, m.line, m.text));
+        }
+        if h.truncated {
+            s.push_str("[truncated]\n");
+        }
+    }
+
+    section(&mut s, "END");
+    s.push_str(&format!("Wrote: {INDEX_TEXT_REL_PATH}\n"));
+    s
+}
+
+/// Build the index and write the text and JSON forms under `<root>/docs/`.
+pub fn write_index(name: &str, root: &Path, profile: &IndexProfile, profile_source: &str) -> Result<IndexSummary, String> {
+    let index = build_index(name, root, profile, profile_source)?;
+    let text = render_index(&index);
+    let json = serde_json::to_string_pretty(&index).map_err(|e| format!("Failed to serialize index: {e}"))?;
+
+    let text_path = root.join(INDEX_TEXT_REL_PATH);
+    let json_path = root.join(INDEX_JSON_REL_PATH);
+    write_atomic(&text_path, text.as_bytes())?;
+    write_atomic(&json_path, format!("{json}\n").as_bytes())?;
+
+    Ok(IndexSummary {
+        name: index.name,
+        repo: index.repo,
+        text_path: text_path.display().to_string(),
+        json_path: json_path.display().to_string(),
+        profile_source: index.profile_source,
+        files_listed: index.listings.iter().map(|l| l.files.len()).sum(),
+        hotspot_matches: index.hotspots.iter().map(|h| h.matches.len()).sum(),
+        bytes: text.len(),
+        generated_at_ms: index.generated_at_ms,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_repo(tag: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("radcontrol-index-{tag}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src")).unwrap();
+        root
+    }
+
+    #[test]
+    fn profile_keys_are_plain_names() {
+        assert!(check_profile_key("tbis").is_ok());
+        assert!(check_profile_key("rad_control-2").is_ok());
+        for bad in ["", "../x", "a/b", "a.b", "a b", "é"] {
+            assert!(check_profile_key(bad).is_err(), "{bad:?}");
+        }
+        assert!(load_profile("/nonexistent", "../../etc/passwd", Path::new("/nonexistent")).is_err());
+    }
+
+    #[test]
+    fn profile_paths_stay_in_the_repo() {
+        let with_preview = |path: &str| IndexProfile {
+            previews: vec![PreviewSpec {
+                path: path.to_string(),
+                max_lines: 10,
+            }],
+            ..Default::default()
+        };
+        assert!(IndexProfile::default().check().is_ok());
+        assert!(with_preview("./src/main.rs").check().is_ok());
+        assert!(with_preview("../secret").check().is_err());
+        assert!(with_preview("src/../../secret").check().is_err());
+        assert!(with_preview("/etc/passwd").check().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_out_of_the_repo_are_not_read() {
+        let root = temp_repo("symlink");
+        let outside = std::env::temp_dir().join(format!("radcontrol-index-outside-{}", std::process::id()));
+        fs::write(&outside, "secret\n").unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("leak")).unwrap();
+        fs::write(root.join("src/lib.rs"), "// TODO: inside\n").unwrap();
+
+        let profile = IndexProfile {
+            previews: vec![
+                PreviewSpec {
+                    path: "leak".to_string(),
+                    max_lines: 10,
+                },
+                PreviewSpec {
+                    path: "src/lib.rs".to_string(),
+                    max_lines: 10,
+                },
+            ],
+            ..Default::default()
+        };
+        let index = build_index("demo", &root, &profile, "test").unwrap();
+        let _ = fs::remove_dir_all(&root);
+        let _ = fs::remove_file(&outside);
+
+        assert!(index.previews[0].lines.is_none());
+        assert_eq!(index.previews[1].lines.as_deref(), Some(&["// TODO: inside".to_string()][..]));
+        assert_eq!(index.hotspots[0].matches.len(), 1);
+    }
+}
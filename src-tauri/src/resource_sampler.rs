@@ -0,0 +1,24 @@
+// Background resource sampler: every SAMPLE_INTERVAL_MS, totals CPU, memory,
+// threads and fds over each project's process tree and emits
+// `resource-usage` with the round's samples.
+
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::commands::registry::load_registry;
+use crate::commands::resources::{ResourceBoard, SAMPLE_INTERVAL_MS};
+use crate::commands::supervisor::Supervisor;
+
+pub const RESOURCE_USAGE_EVENT: &str = "resource-usage";
+
+pub fn spawn(app: AppHandle, board: ResourceBoard, supervisor: Supervisor) {
+    thread::spawn(move || loop {
+        // A broken registry is reported by the registry watcher; skip the round.
+        if let Ok(reg) = load_registry() {
+            let samples = board.sample(&reg.projects, &supervisor);
+            let _ = app.emit(RESOURCE_USAGE_EVENT, samples);
+        }
+        thread::sleep(Duration::from_millis(SAMPLE_INTERVAL_MS));
+    });
+}
@@ -0,0 +1,253 @@
+// Native implementation of the repo snapshot contract that
+// scripts/snapshot_repo_state.sh implements in bash. Same sections, but no
+// dependency on `tree`, a fixed walk order, and no absolute paths or clock
+// readings in the file, so output only changes when the repo itself does.
+
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::fsutil::write_atomic;
+use crate::git::git;
+
+pub const SNAPSHOT_REL_PATH: &str = "docs/_repo_snapshot.txt";
+
+const TREE_DEPTH: usize = 4;
+const TREE_MAX_ENTRIES: usize = 2000;
+const RECENT_COMMITS: usize = 5;
+
+// Listed but never descended into; their contents are caches or history.
+const TREE_OPAQUE_DIRS: &[&str] = &[".git", "node_modules", "target", "dist", ".next"];
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotSummary {
+    pub name: String,
+    pub repo: String,
+    pub path: String,
+    pub branch: Option<String>,
+    pub commit: Option<String>,
+    /// Entries in `git status --porcelain` (staged, unstaged and untracked),
+    /// not counting the snapshot file.
+    pub dirty_files: usize,
+    pub recent_commits: Vec<String>,
+    pub bytes: usize,
+    pub generated_at_ms: u64,
+}
+
+fn tree_lines(dir: &Path, prefix: &str, depth: usize, out: &mut Vec<String>, counts: &mut (usize, usize)) {
+    let Ok(rd) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = rd.filter_map(|e| e.ok()).collect();
+    entries.sort_by_key(|e| e.file_name());
+
+    let n = entries.len();
+    for (i, e) in entries.into_iter().enumerate() {
+        if out.len() >= TREE_MAX_ENTRIES {
+            return;
+        }
+
+        let last = i + 1 == n;
+        let name = e.file_name().to_string_lossy().to_string();
+        // symlinks are listed, not followed
+        let is_dir = e.file_type().map(|t| t.is_dir()).unwrap_or(false);
+
+        out.push(format!("{prefix}{}{name}", if last { "└── " } else { "├── " }));
+
+        if is_dir {
+            counts.0 += 1;
+            if depth > 1 && !TREE_OPAQUE_DIRS.contains(&name.as_str()) {
+                let child_prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+                tree_lines(&e.path(), &child_prefix, depth - 1, out, counts);
+            }
+        } else {
+            counts.1 += 1;
+        }
+    }
+}
+
+/// `tree -a -L <depth>`-style listing, bounded to TREE_MAX_ENTRIES lines.
+pub fn render_tree(root: &Path, depth: usize) -> String {
+    let mut lines = Vec::new();
+    let mut counts = (0, 0);
+    tree_lines(root, "", depth, &mut lines, &mut counts);
+
+    let truncated = lines.len() >= TREE_MAX_ENTRIES;
+    let mut out = String::from(".\n");
+    for l in lines {
+        out.push_str(&l);
+        out.push('\n');
+    }
+    if truncated {
+        out.push_str(&format!("[truncated at {TREE_MAX_ENTRIES} entries]\n"));
+    }
+    out.push_str(&format!("\n{} directories, {} files\n", counts.0, counts.1));
+    out
+}
+
+fn package_json_section(root: &Path) -> String {
+    let path = root.join("package.json");
+    let Ok(s) = fs::read_to_string(&path) else {
+        return "(no package.json)\n".to_string();
+    };
+    let v: serde_json::Value = match serde_json::from_str(&s) {
+        Ok(v) => v,
+        Err(e) => return format!("(invalid package.json: {e})\n"),
+    };
+
+    let mut out = format!(
+        "name: {}\n",
+        v.get("name").and_then(|n| n.as_str()).unwrap_or("(none)")
+    );
+    match v.get("scripts").and_then(|s| s.as_object()) {
+        Some(scripts) if !scripts.is_empty() => {
+            out.push_str("scripts:\n");
+            for (k, cmd) in scripts {
+                out.push_str(&format!("  {k}: {}\n", cmd.as_str().unwrap_or("")));
+            }
+        }
+        _ => out.push_str("scripts: (none)\n"),
+    }
+    out
+}
+
+/// The repo's directory name; the file never records where it was checked out.
+fn repo_name(root: &Path) -> String {
+    root.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| ".".to_string())
+}
+
+/// Build the snapshot text for `root` and write it to
+/// `<root>/docs/_repo_snapshot.txt` atomically.
+pub fn write_snapshot(name: &str, root: &Path) -> Result<SnapshotSummary, String> {
+    if !root.is_dir() {
+        return Err(format!("repo path is not a directory: {}", root.display()));
+    }
+
+    let branch = git(root, &["rev-parse", "--abbrev-ref", "HEAD"]).map(|s| s.trim().to_string());
+    let commit = git(root, &["rev-parse", "HEAD"]).map(|s| s.trim().to_string());
+    // Porcelain output isn't translated, and the snapshot file itself is left
+    // out so writing it never shows up as a change in the next snapshot.
+    let exclude = format!(":(exclude){SNAPSHOT_REL_PATH}");
+    let status = git(root, &["status", "--porcelain", "--", ".", &exclude]);
+    let log = git(root, &["log", &format!("-{RECENT_COMMITS}"), "--oneline"]).unwrap_or_default();
+
+    let mut s = String::new();
+    s.push_str(&format!("=== SNAPSHOT: {name} ===\n"));
+    s.push_str(&format!("repo: {}\n\n", repo_name(root)));
+
+    s.push_str("## identity\n");
+    s.push_str(&format!("branch: {}\n", branch.as_deref().unwrap_or("(no git)")));
+    s.push_str(&format!("commit: {}\n\n", commit.as_deref().unwrap_or("(no git)")));
+
+    s.push_str("## status\n");
+    match status.as_deref() {
+        None => s.push_str("(no git)\n"),
+        Some(st) if st.trim().is_empty() => s.push_str("(clean)\n"),
+        Some(st) => s.push_str(st),
+    }
+    s.push('\n');
+
+    s.push_str("## recent commits\n");
+    s.push_str(&log);
+    s.push('\n');
+
+    s.push_str(&format!("## tree (depth {TREE_DEPTH})\n"));
+    s.push_str(&render_tree(root, TREE_DEPTH));
+    s.push('\n');
+
+    s.push_str("## package.json (name + scripts)\n");
+    s.push_str(&package_json_section(root));
+    s.push('\n');
+
+    s.push_str("## verification\n");
+    s.push_str("lint: not run\n");
+    s.push_str("build: not run\n");
+
+    let path = root.join(SNAPSHOT_REL_PATH);
+    write_atomic(&path, s.as_bytes())?;
+
+    Ok(SnapshotSummary {
+        name: name.to_string(),
+        repo: root.display().to_string(),
+        path: path.display().to_string(),
+        branch,
+        commit,
+        dirty_files: status.as_deref().unwrap_or_default().lines().filter(|l| !l.trim().is_empty()).count(),
+        recent_commits: log.lines().map(str::to_string).collect(),
+        bytes: s.len(),
+        generated_at_ms: chrono::Local::now().timestamp_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_is_stable_and_machine_independent() {
+        let root = std::env::temp_dir().join(format!("radcontrol-snapshot-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(root.join("package.json"), r#"{"name":"demo","scripts":{"dev":"vite"}}"#).unwrap();
+        // The snapshot lists itself in the tree; make it exist from the start.
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join(SNAPSHOT_REL_PATH), "").unwrap();
+
+        write_snapshot("Demo", &root).unwrap();
+        let first = fs::read_to_string(root.join(SNAPSHOT_REL_PATH)).unwrap();
+        write_snapshot("Demo", &root).unwrap();
+        let second = fs::read_to_string(root.join(SNAPSHOT_REL_PATH)).unwrap();
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(first, second);
+        assert!(!first.contains(&root.display().to_string()));
+        assert!(first.contains(&format!("repo: radcontrol-snapshot-{}\n", std::process::id())));
+        assert!(first.contains("  dev: vite\n"));
+    }
+
+    #[test]
+    fn status_is_porcelain_and_ignores_the_snapshot() {
+        let root = std::env::temp_dir().join(format!("radcontrol-snapshot-git-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        let run = |args: &[&str]| {
+            let ok = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&root)
+                .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+                .args(args)
+                .output()
+                .unwrap()
+                .status
+                .success();
+            assert!(ok, "git {args:?}");
+        };
+        run(&["init", "-q"]);
+        run(&["add", "-A"]);
+        run(&["commit", "-qm", "init"]);
+
+        // Untracked, then tracked and rewritten: never reported either way.
+        let clean = write_snapshot("Demo", &root).unwrap();
+        run(&["add", "-A"]);
+        run(&["commit", "-qm", "snapshot"]);
+        fs::write(root.join(SNAPSHOT_REL_PATH), "stale\n").unwrap();
+        let still_clean = write_snapshot("Demo", &root).unwrap();
+        let clean_text = fs::read_to_string(root.join(SNAPSHOT_REL_PATH)).unwrap();
+
+        fs::write(root.join("src/main.rs"), "fn main() { println!(); }\n").unwrap();
+        let dirty = write_snapshot("Demo", &root).unwrap();
+        let dirty_text = fs::read_to_string(root.join(SNAPSHOT_REL_PATH)).unwrap();
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(clean.dirty_files, 0);
+        assert_eq!(still_clean.dirty_files, 0);
+        assert!(clean_text.contains("## status\n(clean)\n"));
+        assert_eq!(dirty.dirty_files, 1);
+        assert!(dirty_text.contains("## status\n M src/main.rs\n"));
+        let section = dirty_text.split("## status\n").nth(1).unwrap().split("\n## ").next().unwrap();
+        assert!(!section.contains("_repo_snapshot"));
+    }
+}
@@ -0,0 +1,47 @@
+// Background status poller: probes ports and health for every registered
+// project on the StatusBoard interval and emits `status-changed` only when a
+// project transitions (e.g. stopped -> running, running -> unhealthy).
+
+use serde::Serialize;
+use std::thread;
+use tauri::{AppHandle, Emitter};
+
+use crate::commands::registry::load_registry;
+use crate::commands::status::{project_statuses, ProjectStatus, StatusBoard, StatusTransition};
+
+pub const STATUS_CHANGED_EVENT: &str = "status-changed";
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusChanged {
+    pub transitions: Vec<StatusTransition>,
+    /// Full current state, so listeners don't need a follow-up call.
+    pub statuses: Vec<ProjectStatus>,
+}
+
+fn poll_once(app: &AppHandle, board: &StatusBoard) {
+    // A broken registry is reported by the registry watcher; skip the round.
+    let Ok(reg) = load_registry() else {
+        return;
+    };
+
+    let transitions = board.update(project_statuses(&reg.projects));
+    if transitions.is_empty() {
+        return;
+    }
+
+    let _ = app.emit(
+        STATUS_CHANGED_EVENT,
+        StatusChanged {
+            transitions,
+            statuses: board.snapshot(),
+        },
+    );
+}
+
+pub fn spawn(app: AppHandle, board: StatusBoard) {
+    thread::spawn(move || loop {
+        poll_once(&app, &board);
+        board.wait_for_next_poll();
+    });
+}
@@ -1,6 +1,7 @@
 import { useEffect, useMemo, useRef, useState } from "react";
 import "./App.css";
 import { invoke, isTauri } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { getCurrentWindow, LogicalSize } from "@tauri-apps/api/window";
 import { openUrl } from "@tauri-apps/plugin-opener";
 
@@ -12,6 +13,26 @@ import type {
   AddProjectPayload,
   ProjectRow,
   PortStatus,
+  RegistryLoad,
+  PortOwners,
+  KillReport,
+  HealthStatus,
+  ProjectStatus,
+  SnapshotSummary,
+  IndexSummary,
+  ProjectGitStatus,
+  CommitPreview,
+  CommitResult,
+  DiffSummary,
+  VerbInfo,
+  VerbLoad,
+  PreparedVerb,
+  WorkflowStepEvent,
+  WorkflowReport,
+  SupervisedProcess,
+  LogLine,
+  ProjectResources,
+  PanicReport,
 } from "./components/projects/types";
 import {
   fmtErr,
@@ -69,34 +90,6 @@ async function copyText(text: string) {
   }
 }
 
-function parseRegistryMaybeDoubleEncoded(raw: string): unknown[] {
-  let first: unknown;
-  try {
-    first = JSON.parse(raw);
-  } catch (e) {
-    throw new Error(`Registry response was not valid JSON: ${String(e)}`);
-  }
-
-  let reg: unknown = first;
-  if (typeof first === "string") {
-    try {
-      reg = JSON.parse(first);
-    } catch (e) {
-      throw new Error(
-        `Registry double-encoded JSON could not be parsed: ${String(e)}`,
-      );
-    }
-  }
-
-  if (!Array.isArray(reg)) {
-    throw new Error(
-      `Registry parsed but was not an array (type=${typeof reg}).`,
-    );
-  }
-
-  return reg as unknown[];
-}
-
 function extractFirstHttpUrl(s: string): string | null {
   if (!s) return null;
   const m = s.match(/https?:\/\/localhost:\d+(?:\/[^\s]*)?/);
@@ -130,25 +123,6 @@ async function tryAutoOpen(url: string) {
   }
 }
 
-// O2 port_status.<port> returns JSON like: {"port":3000,"listening":true}
-type O2PortStatusJson = { port?: number; listening?: boolean };
-
-function parsePortStatusJson(out: string, port: number): PortStatus {
-  try {
-    const obj = JSON.parse((out || "").trim()) as O2PortStatusJson;
-    const listening = Boolean(obj?.listening);
-    return { port, listening, pid: null, cmd: null, err: null };
-  } catch {
-    return {
-      port,
-      listening: false,
-      pid: null,
-      cmd: null,
-      err: "invalid json",
-    };
-  }
-}
-
 function registryPortForKey(reg: unknown, key: string): number | null {
   if (!Array.isArray(reg)) return null;
 
@@ -162,30 +136,74 @@ function registryPortForKey(reg: unknown, key: string): number | null {
     : null;
 }
 
-async function invokeText(cmd: string, payload?: Record<string, unknown>) {
-  const out = (await invoke(cmd, payload ? payload : undefined)) as unknown;
-
-  // Most commands return plain string. run_o2 returns RunO2Result object.
-  if (typeof out === "string") return out;
-
-  if (out && typeof out === "object") {
-    const o = out as Record<string, unknown>;
+// Emitted by the backend registry watcher (src-tauri/src/registry_watch.rs).
+type RegistryChanged = {
+  ok: boolean;
+  registry: RegistryLoad | null;
+  error: string | null;
+};
+
+// Emitted by the backend status poller (src-tauri/src/status_poller.rs).
+type StatusChanged = {
+  transitions: { key: string; from: ProjectStatus["state"] | null; to: ProjectStatus["state"] }[];
+  statuses: ProjectStatus[];
+};
+
+// Named verb arguments, validated against the verb's declared params and
+// passed to run_o2.sh as O2_ARG_<NAME> (see src-tauri/src/commands/verbs.rs).
+type O2Args = Record<string, string | number | null>;
+
+// Emitted by run_o2_stream (see src-tauri/src/commands/o2.rs).
+type O2OutputEvent = { jobId: string; stream: "stdout" | "stderr"; line: string };
+type O2FinishedEvent = {
+  jobId: string;
+  verb: string;
+  ok: boolean;
+  code: number;
+  stdout: string;
+  stderr: string;
+  cancelled: boolean;
+  timed_out: boolean;
+  elapsed_ms: number;
+};
+
+/**
+ * Run a verb via run_o2_stream, forwarding each output line as it arrives.
+ * Events may land before invoke() resolves with the job id, so they are
+ * buffered until the id is known.
+ */
+async function runO2Streaming(
+  verb: string,
+  onLine: (ev: O2OutputEvent) => void,
+  onStart?: (jobId: string) => void,
+  opts?: { args?: O2Args; confirmToken?: string; confirmPhrase?: string },
+): Promise<O2FinishedEvent> {
+  let jobId: string | null = null;
+  const early: O2OutputEvent[] = [];
+  const earlyDone: O2FinishedEvent[] = [];
+  let resolveDone: (ev: O2FinishedEvent) => void = () => {};
+  const done = new Promise<O2FinishedEvent>((r) => (resolveDone = r));
+
+  const unOutput = await listen<O2OutputEvent>("o2-output", (e) => {
+    if (jobId === null) early.push(e.payload);
+    else if (e.payload.jobId === jobId) onLine(e.payload);
+  });
+  const unFinished = await listen<O2FinishedEvent>("o2-finished", (e) => {
+    if (jobId === null) earlyDone.push(e.payload);
+    else if (e.payload.jobId === jobId) resolveDone(e.payload);
+  });
 
-    // Prefer stdout when present (RunO2Result)
-    if (typeof o.stdout === "string") return o.stdout;
-
-    // Some commands may return { output: "..." } or similar
-    if (typeof o.output === "string") return o.output;
-
-    // Last resort: stringify objects so callers can parse deterministically
-    try {
-      return JSON.stringify(o);
-    } catch {
-      return "[unstringifiable object]";
-    }
+  try {
+    jobId = (await invoke("run_o2_stream", { verb, ...opts })) as string;
+    onStart?.(jobId);
+    early.filter((ev) => ev.jobId === jobId).forEach(onLine);
+    const fin = earlyDone.find((ev) => ev.jobId === jobId);
+    if (fin) resolveDone(fin);
+    return await done;
+  } finally {
+    unOutput();
+    unFinished();
   }
-
-  return (out ?? "").toString();
 }
 
 export default function App() {
@@ -198,6 +216,7 @@ export default function App() {
     setLog((prev) => (prev ? prev + "\n" + s : s));
 
   const [lastUrl, setLastUrl] = useState<string | null>(null);
+  const [currentJobId, setCurrentJobId] = useState<string | null>(null);
 
   // --- Window sizing ---
   useEffect(() => {
@@ -238,6 +257,30 @@ export default function App() {
   const [projects, setProjects] = useState<ProjectRow[]>([]);
   const [rawRegistry, setRawRegistry] = useState<unknown[]>([]);
   const [showAddProject, setShowAddProject] = useState(false);
+  // Row open in the project modal for editing; null when adding.
+  const [editingProject, setEditingProject] = useState<ProjectRow | null>(
+    null,
+  );
+
+  // Verb metadata (descriptions, destructive flag, ...) from the backend
+  // catalog; reloaded with the registry since hooks contribute verbs.
+  const [verbs, setVerbs] = useState<Record<string, VerbInfo>>({});
+
+  async function loadVerbs() {
+    try {
+      const load = await invoke<VerbLoad>("o2_list_verbs");
+      const next: Record<string, VerbInfo> = {};
+      load.verbs.forEach((v) => (next[v.verb] = v));
+      setVerbs(next);
+      load.errors.forEach((e) =>
+        appendLog(
+          `[verbs] skipped ${e.source}${e.project ? ` hook in ${e.project}` : ""}${e.verb ? ` (${e.verb})` : ""}: ${e.message}`,
+        ),
+      );
+    } catch (e) {
+      appendLog("\n[verbs] failed:\n" + fmtErr(e));
+    }
+  }
 
   const loadRegistryOnceRef = useRef(false);
   const loadRegistryInFlightRef = useRef<Promise<void> | null>(null);
@@ -247,14 +290,19 @@ export default function App() {
 
     loadRegistryInFlightRef.current = (async () => {
       try {
-        const raw = await invokeText("o2_list_projects");
-        const reg = parseRegistryMaybeDoubleEncoded(raw);
+        const reg = (await invoke("o2_list_projects")) as RegistryLoad;
 
-        setRawRegistry(reg);
-        const rows = registryToProjects(reg);
+        setRawRegistry(reg.projects);
+        const rows = registryToProjects(reg.projects);
         setProjects(rows);
 
         appendLog(`[registry] loaded ${rows.length} project(s)`);
+        void loadVerbs();
+        reg.errors.forEach((e) =>
+          appendLog(
+            `[registry] skipped row ${e.index}${e.key ? ` (${e.key})` : ""}: ${e.message}`,
+          ),
+        );
       } catch (e) {
         appendLog("\n[registry] failed:\n" + fmtErr(e));
         setRawRegistry([]);
@@ -273,6 +321,31 @@ export default function App() {
     void loadRegistry();
   }, []);
 
+  // Backend watches projects.json and re-parses it on change.
+  useEffect(() => {
+    const un = listen<RegistryChanged>("registry-changed", (e) => {
+      const ev = e.payload;
+      if (ev.ok && ev.registry) {
+        setRawRegistry(ev.registry.projects);
+        setProjects(registryToProjects(ev.registry.projects));
+        appendLog(
+          `[registry] projects.json changed; ${ev.registry.projects.length} project(s)`,
+        );
+        void loadVerbs();
+        ev.registry.errors.forEach((er) =>
+          appendLog(
+            `[registry] skipped row ${er.index}${er.key ? ` (${er.key})` : ""}: ${er.message}`,
+          ),
+        );
+      } else {
+        appendLog(`\n[registry] reload failed:\n${ev.error ?? "unknown error"}`);
+      }
+    });
+    return () => {
+      void un.then((f) => f());
+    };
+  }, []);
+
   const usedPorts = useMemo(() => {
     const s = new Set<number>();
     projects.forEach((p) => {
@@ -294,6 +367,7 @@ export default function App() {
   const [ports, setPorts] = useState<Record<number, PortStatus | undefined>>(
     {},
   );
+  const [health, setHealth] = useState<Record<string, HealthStatus>>({});
 
   const PORTS = useMemo(() => {
     const s = new Set<number>();
@@ -307,6 +381,161 @@ export default function App() {
     return Array.from(s.values()).sort((a, b) => a - b);
   }, [projects, rawRegistry]);
 
+  function applyStatuses(statuses: ProjectStatus[]) {
+    const next: Record<number, PortStatus> = {};
+    const nextHealth: Record<string, HealthStatus> = {};
+    statuses.forEach((st) => {
+      if (st.port) next[st.port.port] = st.port;
+      if (st.health) nextHealth[st.key] = st.health;
+    });
+    setPorts(next);
+    setHealth(nextHealth);
+  }
+
+  // The backend poller pushes status-changed whenever a project transitions,
+  // including projects started or stopped from a terminal.
+  useEffect(() => {
+    const un = listen<StatusChanged>("status-changed", (e) => {
+      applyStatuses(e.payload.statuses);
+      e.payload.transitions
+        .filter((t) => t.from !== null)
+        .forEach((t) => appendLog(`[status] ${t.key}: ${t.from} → ${t.to}`));
+    });
+    return () => {
+      void un.then((f) => f());
+    };
+  }, []);
+
+  // Workflows run in the background; log their progress as it arrives.
+  useEffect(() => {
+    const unStep = listen<WorkflowStepEvent>("workflow-step", (e) => {
+      const { workflow, total, step } = e.payload;
+      const tag = `[${workflow} ${step.index + 1}/${total}] ${step.name}`;
+      if (step.status === "running") appendLog(`${tag} …`);
+      else
+        appendLog(
+          `${tag}: ${step.status}${step.error ? ` (${step.error})` : ""}`,
+        );
+    });
+    const unDone = listen<WorkflowReport>("workflow-finished", (e) => {
+      const r = e.payload;
+      appendLog(
+        `[${r.workflow}] ${r.project} ${r.ok ? "done" : "finished with failures"} in ${r.endedAtMs - r.startedAtMs}ms`,
+      );
+      void refreshGit();
+    });
+    return () => {
+      void unStep.then((f) => f());
+      void unDone.then((f) => f());
+    };
+  }, []);
+
+  // --- Supervisor ---
+  const [supervised, setSupervised] = useState<
+    Record<string, SupervisedProcess>
+  >({});
+
+  useEffect(() => {
+    void invoke<SupervisedProcess[]>("o2_supervisor_list")
+      .then((rows) =>
+        setSupervised(Object.fromEntries(rows.map((r) => [r.key, r]))),
+      )
+      .catch((e) => appendLog("\n[supervisor] list failed:\n" + fmtErr(e)));
+
+    const un = listen<SupervisedProcess>("supervisor-changed", (e) => {
+      const sp = e.payload;
+      setSupervised((prev) => ({ ...prev, [sp.key]: sp }));
+      if (sp.state === "exited")
+        appendLog(
+          `[supervisor] ${sp.key} exited${sp.exitCode != null ? ` (code ${sp.exitCode})` : ""}`,
+        );
+    });
+    return () => {
+      void un.then((f) => f());
+    };
+  }, []);
+
+  async function superviseProject(
+    p: ProjectRow,
+    action: "start" | "stop" | "restart",
+  ): Promise<boolean> {
+    appendLog(`\n[supervisor] ${action} ${p.label}`);
+    try {
+      const sp = await invoke<SupervisedProcess>(`o2_supervisor_${action}`, {
+        key: p.key,
+      });
+      appendLog(
+        sp.state === "exited"
+          ? `[supervisor] ${p.key} stopped`
+          : `[supervisor] ${p.key} running as pid ${sp.pid}: ${sp.command.join(" ")}`,
+      );
+      return true;
+    } catch (e) {
+      appendLog(`\n[supervisor] ${action} failed:\n` + fmtErr(e));
+      return false;
+    }
+  }
+
+  // Recent output for a project, from the backend's log ring (it survives
+  // the process and app restarts).
+  async function showLogs(p: ProjectRow) {
+    try {
+      const lines = await invoke<LogLine[]>("o2_logs_tail", {
+        source: p.key,
+        n: 200,
+      });
+      if (lines.length === 0) {
+        appendLog(`[logs] ${p.key}: no output captured yet`);
+        return;
+      }
+      appendLog(
+        `\n[logs] ${p.key}: last ${lines.length} line(s)\n` +
+          lines
+            .map((l) =>
+              l.stream === "stdout" ? l.line : `[${l.stream}] ${l.line}`,
+            )
+            .join("\n"),
+      );
+    } catch (e) {
+      appendLog("\n[logs] ERROR:\n" + fmtErr(e));
+    }
+  }
+
+  // --- Resources ---
+  // The backend samples every few seconds and pushes resource-usage.
+  const [resources, setResources] = useState<
+    Record<string, ProjectResources>
+  >({});
+
+  useEffect(() => {
+    const un = listen<ProjectResources[]>("resource-usage", (e) => {
+      setResources(Object.fromEntries(e.payload.map((r) => [r.key, r])));
+    });
+    return () => {
+      void un.then((f) => f());
+    };
+  }, []);
+
+  // --- Git ---
+  const [gitStatus, setGitStatus] = useState<
+    Record<string, ProjectGitStatus>
+  >({});
+
+  async function refreshGit() {
+    try {
+      const rows = await invoke<ProjectGitStatus[]>("o2_git_status");
+      const next: Record<string, ProjectGitStatus> = {};
+      rows.forEach((r) => (next[r.key] = r));
+      setGitStatus(next);
+    } catch {
+      setGitStatus({});
+    }
+  }
+
+  useEffect(() => {
+    void refreshGit();
+  }, [projects]);
+
   // Coalesce refresh calls deterministically.
   const refreshInFlightRef = useRef<Promise<void> | null>(null);
 
@@ -317,30 +546,26 @@ export default function App() {
     refreshInFlightRef.current = (async () => {
       setPortsBusy(true);
       try {
-        const results = await Promise.all(
-          PORTS.map(async (p) => {
-            try {
-              const out = await invokeText("run_o2", {
-                verb: `port_status.${p}`,
-              });
-              return parsePortStatusJson(out, p);
-            } catch (e) {
-              return {
-                port: p,
-                listening: false,
-                pid: null,
-                cmd: null,
-                err: fmtErr(e),
-              } as PortStatus;
-            }
-          }),
-        );
-
-        const next: Record<number, PortStatus> = {};
-        results.forEach((r) => {
-          if (typeof r.port === "number") next[r.port] = r;
-        });
-        setPorts(next);
+        try {
+          // One /proc pass in the backend (pid + cmdline included), plus an
+          // HTTP check for every listening project with a url.
+          applyStatuses(
+            (await invoke("o2_project_status")) as ProjectStatus[],
+          );
+        } catch (e) {
+          const next: Record<number, PortStatus> = {};
+          PORTS.forEach((p) => {
+            next[p] = {
+              port: p,
+              listening: false,
+              pid: null,
+              cmd: null,
+              err: fmtErr(e),
+            };
+          });
+          setPorts(next);
+          setHealth({});
+        }
       } finally {
         setPortsBusy(false);
         refreshInFlightRef.current = null;
@@ -362,53 +587,298 @@ export default function App() {
     const s = ports[p.port];
     if (!s) return { pill: "pillWarn", text: "UNKNOWN" };
 
-    return s.listening
-      ? { pill: "pillOn", text: "RUNNING" }
-      : { pill: "pillOff", text: "STOPPED" };
+    if (!s.listening) return { pill: "pillOff", text: "STOPPED" };
+
+    const h = health[p.key];
+    return h && h.checked && !h.ok
+      ? { pill: "pillWarn", text: h.status ? `UNHEALTHY ${h.status}` : "UNHEALTHY" }
+      : { pill: "pillOn", text: "RUNNING" };
   }
 
   // --- O2 ---
-  async function runO2(title: string, key?: string): Promise<string | null> {
+  // Destructive verbs need a confirmation token from o2_prepare_verb, plus
+  // the verb's confirm phrase when it declares one.
+  async function confirmVerb(
+    key: string,
+    args?: O2Args,
+  ): Promise<{ confirmToken: string; confirmPhrase?: string } | null> {
+    const prep = await invoke<PreparedVerb>("o2_prepare_verb", {
+      verb: key,
+      args,
+    });
+    const what = prep.description ?? prep.verb;
+
+    if (prep.confirmPhrase) {
+      const typed = window.prompt(
+        `${what} is destructive.\n\nType "${prep.confirmPhrase}" to continue:`,
+      );
+      if (typed === null) return null;
+      return { confirmToken: prep.token, confirmPhrase: typed };
+    }
+    if (!window.confirm(`${what} is destructive. Continue?`)) return null;
+    return { confirmToken: prep.token };
+  }
+
+  async function runO2(
+    title: string,
+    key?: string,
+    args?: O2Args,
+  ): Promise<string | null> {
     if (!key || busy) return null;
 
+    const meta = verbs[key];
+    const label = meta?.description ?? title;
+
+    let confirm: { confirmToken: string; confirmPhrase?: string } | null = null;
+    if (meta?.destructive) {
+      try {
+        confirm = await confirmVerb(key, args);
+      } catch (e) {
+        appendLog(`\n[o2] ${label}: prepare failed:\n` + fmtErr(e));
+        return null;
+      }
+      if (!confirm) return null;
+    }
+
     setBusy(true);
-    appendLog(`\n[o2] ${title} → run_o2("${key}")\n`);
+    appendLog(
+      `\n[o2] ${label} → run_o2_stream("${key}"${args ? `, ${JSON.stringify(args)}` : ""})\n`,
+    );
     try {
-      const out = await invokeText("run_o2", { verb: key });
-      const text = (out ?? "(no output)").toString();
-      appendLog(text);
-      return text;
+      const fin = await runO2Streaming(
+        key,
+        (ev) =>
+          appendLog(ev.stream === "stderr" ? `[stderr] ${ev.line}` : ev.line),
+        setCurrentJobId,
+        { args, ...confirm },
+      );
+      if (!fin.stdout && !fin.stderr) appendLog("(no output)");
+      if (fin.cancelled) appendLog("[o2] cancelled");
+      else if (fin.timed_out)
+        appendLog(`[o2] timed out after ${Math.round(fin.elapsed_ms / 1000)}s`);
+      else if (!fin.ok) appendLog(`[o2] exit code ${fin.code}`);
+      return fin.stdout;
     } catch (e) {
       appendLog("\n[o2] ERROR:\n" + fmtErr(e));
       return null;
     } finally {
       setBusy(false);
-      try {
-        await refreshPorts();
-      } catch {
-        // ignore
+      setCurrentJobId(null);
+      void refreshGit();
+      if (meta?.refreshPorts !== false) {
+        try {
+          await refreshPorts();
+        } catch {
+          // ignore
+        }
       }
     }
   }
 
+  async function cancelCurrentJob() {
+    if (!currentJobId) return;
+    try {
+      await invoke("o2_cancel", { jobId: currentJobId });
+      appendLog(`[o2] cancel requested (${currentJobId})`);
+    } catch (e) {
+      appendLog("\n[o2] cancel failed:\n" + fmtErr(e));
+    }
+  }
+
+  // Projects without their own snapshot verb get the native snapshot.
+  async function snapshotProject(p: ProjectRow) {
+    if (p.o2SnapshotKey) {
+      void runO2(`Snapshot ${p.label}`, p.o2SnapshotKey);
+      return;
+    }
+    if (busy) return;
+
+    setBusy(true);
+    appendLog(`\n[snapshot] ${p.label} → o2_snapshot_project("${p.key}")\n`);
+    try {
+      const s = await invoke<SnapshotSummary>("o2_snapshot_project", {
+        key: p.key,
+      });
+      appendLog(
+        `[snapshot] wrote ${s.path} (${s.branch ?? "no git"}, ${s.dirtyFiles} dirty)`,
+      );
+    } catch (e) {
+      appendLog("\n[snapshot] ERROR:\n" + fmtErr(e));
+    } finally {
+      setBusy(false);
+    }
+  }
+
+  // Same fallback for the repo map: native index when there's no map verb.
+  async function mapProject(p: ProjectRow) {
+    if (p.o2MapKey) {
+      void runO2(`${p.label} Map`, p.o2MapKey);
+      return;
+    }
+    if (busy) return;
+
+    setBusy(true);
+    appendLog(`\n[index] ${p.label} → o2_index_project("${p.key}")\n`);
+    try {
+      const s = await invoke<IndexSummary>("o2_index_project", { key: p.key });
+      appendLog(
+        `[index] wrote ${s.textPath} (${s.filesListed} files, ${s.hotspotMatches} hotspot hits, profile ${s.profileSource})`,
+      );
+    } catch (e) {
+      appendLog("\n[index] ERROR:\n" + fmtErr(e));
+    } finally {
+      setBusy(false);
+    }
+  }
+
+  async function sessionStart(p: ProjectRow) {
+    appendLog(`\n[workflow] ${p.label} → session_start`);
+    try {
+      await invoke<string>("o2_run_workflow", {
+        id: "session_start",
+        project: p.key,
+      });
+    } catch (e) {
+      appendLog("\n[workflow] ERROR:\n" + fmtErr(e));
+    }
+  }
+
+  // Commit: preview what the verb would pick up, ask for a message, then run
+  // the verb with the message as a structured argument. The preview token
+  // makes the backend refuse if the tree changed while the prompt was open.
+  async function commitProject(p: ProjectRow) {
+    if (!p.o2CommitKey || busy) return;
+
+    let preview: CommitPreview;
+    try {
+      preview = await invoke<CommitPreview>("o2_commit_preview", {
+        key: p.key,
+      });
+    } catch (e) {
+      appendLog(`\n[commit] ${p.label} preview failed:\n` + fmtErr(e));
+      return;
+    }
+
+    const fileLines = (label: string, d: DiffSummary) =>
+      d.files.map(
+        (f) =>
+          `  ${label} ${f.path} (+${f.insertions ?? "bin"} -${f.deletions ?? "bin"})`,
+      );
+    const lines = [
+      ...fileLines("staged  ", preview.staged),
+      ...fileLines("modified", preview.unstaged),
+      ...preview.untracked.map((f) => `  untracked ${f}`),
+    ];
+    if (lines.length === 0) {
+      appendLog(`[commit] ${p.label}: nothing to commit`);
+      return;
+    }
+
+    appendLog(
+      `\n[commit] ${p.label} on ${preview.branch ?? "detached HEAD"}: ` +
+        `${lines.length} file(s), ` +
+        `+${preview.staged.insertions + preview.unstaged.insertions} ` +
+        `-${preview.staged.deletions + preview.unstaged.deletions}`,
+    );
+    lines.forEach((l) => appendLog(l));
+    if (preview.diff) appendLog(preview.diff);
+    if (preview.diffTruncated) appendLog("[commit] (diff truncated)");
+
+    const message = window.prompt(
+      `Commit ${lines.length} file(s) in ${p.label}?\n\n` +
+        `${lines.slice(0, 15).join("\n")}` +
+        `${lines.length > 15 ? `\n  … ${lines.length - 15} more` : ""}\n\n` +
+        "Commit message:",
+    );
+    if (!message?.trim()) return;
+
+    setBusy(true);
+    appendLog(`[commit] ${p.label} → o2_commit("${p.key}")`);
+    try {
+      const r = await invoke<CommitResult>("o2_commit", {
+        key: p.key,
+        message,
+        token: preview.token,
+      });
+      if (r.run.stdout) appendLog(r.run.stdout);
+      if (r.run.stderr) appendLog(`[stderr] ${r.run.stderr}`);
+      appendLog(
+        r.committed
+          ? `[commit] ${p.label} committed ${r.newHead?.slice(0, 12)}`
+          : `[commit] ${p.label}: no new commit (exit code ${r.run.code})`,
+      );
+    } catch (e) {
+      appendLog("\n[commit] ERROR:\n" + fmtErr(e));
+    } finally {
+      setBusy(false);
+      void refreshGit();
+    }
+  }
+
   async function restartRadcontrol() {
-    void runO2("Restart RadControl + Refresh Status", "radcontrol.dev_strict");
+    void runO2("Restart RadControl", "radcontrol.dev_strict");
   }
 
-  const startRecheckTimerRef = useRef<number | null>(null);
+  // Panic: dry run first so the confirm lists exactly what will be stopped;
+  // the real run writes an incident report under the app data dir.
+  async function panicStop() {
+    if (busy) return;
 
-  useEffect(() => {
-    return () => {
-      if (startRecheckTimerRef.current !== null) {
-        window.clearTimeout(startRecheckTimerRef.current);
-      }
-    };
-  }, []);
+    let plan: PanicReport;
+    try {
+      plan = await invoke<PanicReport>("o2_panic_stop", { dryRun: true });
+    } catch (e) {
+      appendLog("\n[panic] dry run failed:\n" + fmtErr(e));
+      return;
+    }
 
-  async function workOnProject(p: ProjectRow) {
-    if (!p?.o2StartKey) return;
+    const lines = plan.targets.map((t) => {
+      const what =
+        t.kind === "job"
+          ? `job ${t.jobId}`
+          : `${t.kind} ${t.key ?? ""}${t.port ? ` :${t.port}` : ""}`;
+      const pids = t.processes.map((p) => p.pid).join(", ");
+      const skip = t.error
+        ? ` — ERROR: ${t.error}`
+        : t.skipReason
+          ? ` — SKIPPED: ${t.skipReason}`
+          : "";
+      return `  ${t.order}. ${what}${pids ? ` (pids ${pids})` : ""}${skip}`;
+    });
+    appendLog(`\n[panic] would stop:\n${lines.join("\n") || "  (nothing)"}`);
+    if (plan.registryError) {
+      appendLog(`[panic] project ports not checked — registry: ${plan.registryError}`);
+    }
+    if (!plan.targets.some((t) => !t.skipReason && !t.error)) return;
+    if (!window.confirm(`Stop everything?\n\n${lines.join("\n")}`)) return;
 
-    const out = await runO2(`Start ${p.label}`, p.o2StartKey);
+    setBusy(true);
+    try {
+      const r = await invoke<PanicReport>("o2_panic_stop", { dryRun: false });
+      r.outcomes.forEach((o) =>
+        appendLog(`[panic] ${o.order}. ${o.ok ? "ok" : "FAILED"}: ${o.detail}`),
+      );
+      appendLog(`[panic] incident report: ${r.reportPath ?? "(not written)"}`);
+    } catch (e) {
+      appendLog("\n[panic] ERROR:\n" + fmtErr(e));
+    } finally {
+      setBusy(false);
+      void refreshPorts();
+    }
+  }
+
+  // Rows with a start spec are launched by the supervisor; the rest go
+  // through their O2 start verb.
+  async function workOnProject(p: ProjectRow) {
+    let out: string | null = null;
+    if (p?.start) {
+      const running = supervised[p.key]?.state === "running";
+      if (!running && !(await superviseProject(p, "start"))) return;
+    } else if (p?.o2StartKey) {
+      out = await runO2(`Start ${p.label}`, p.o2StartKey);
+    } else {
+      return;
+    }
 
     const urlFromOut = out ? extractFirstHttpUrl(out) : null;
     const fallbackUrl =
@@ -420,13 +890,8 @@ export default function App() {
     setLastUrl(finalUrl);
     void copyText(finalUrl);
 
-    // Post-start recheck: the port may begin listening shortly AFTER the start returns.
-    if (startRecheckTimerRef.current !== null) {
-      window.clearTimeout(startRecheckTimerRef.current);
-    }
-    startRecheckTimerRef.current = window.setTimeout(() => {
-      void refreshPorts();
-    }, 1200);
+    // No post-start recheck needed: the backend poller emits status-changed
+    // once the port starts listening.
 
     try {
       await tryAutoOpen(finalUrl);
@@ -437,7 +902,61 @@ export default function App() {
   }
 
   async function freePort(port: number) {
-    void runO2("Kill requested", `kill_port.${port}`);
+    if (busy) return;
+
+    let owners: PortOwners;
+    try {
+      owners = (await invoke("o2_port_owners", { port })) as PortOwners;
+    } catch (e) {
+      appendLog(`\n[kill] :${port} preview failed:\n` + fmtErr(e));
+      return;
+    }
+
+    if (owners.processes.length === 0) {
+      appendLog(`[kill] :${port} has no visible owner; nothing to stop`);
+      void refreshPorts();
+      return;
+    }
+
+    const lines = owners.processes.map(
+      (p) => `  ${p.pid} (ppid ${p.ppid}) ${p.cmdline ?? "?"}`,
+    );
+    let force = false;
+    if (owners.protectedReason) {
+      force = window.confirm(
+        `Port ${port}: ${owners.protectedReason}.\n\nKill anyway?\n\n${lines.join("\n")}`,
+      );
+      if (!force) return;
+    } else if (
+      !window.confirm(`Stop these processes on :${port}?\n\n${lines.join("\n")}`)
+    ) {
+      return;
+    }
+
+    setBusy(true);
+    appendLog(`\n[kill] :${port} → ${owners.processes.length} process(es)`);
+    lines.forEach((l) => appendLog(l));
+    try {
+      // Exactly the processes shown above; the backend refuses if the
+      // port has changed hands since.
+      const expected = owners.processes.map((p) => ({
+        pid: p.pid,
+        startedAtMs: p.startedAtMs ?? null,
+      }));
+      const r = (await invoke("o2_kill_port", {
+        port,
+        expected,
+        force,
+      })) as KillReport;
+      appendLog(
+        `[kill] :${port} exited=${r.exited.join(",") || "-"} killed=${r.killed.join(",") || "-"} survivors=${r.survivors.join(",") || "-"}`,
+      );
+    } catch (e) {
+      appendLog(`\n[kill] :${port} failed:\n` + fmtErr(e));
+    } finally {
+      setBusy(false);
+      void refreshPorts();
+    }
   }
 
   async function createProject(payload: AddProjectPayload) {
@@ -456,7 +975,10 @@ export default function App() {
     const entry: Record<string, unknown> = {
       key: payload.key,
       label: payload.label,
-      repoHint: payload.repoPath,
+      repoHint: payload.repoHint || payload.repoPath,
+      repoPath: payload.repoPath,
+      org: payload.org,
+      kind: payload.kind,
     };
 
     if (typeof payload.port === "number") entry.port = payload.port;
@@ -468,20 +990,80 @@ export default function App() {
     if (payload.o2MapKey) entry.o2MapKey = payload.o2MapKey;
     if (payload.o2ProofPackKey) entry.o2ProofPackKey = payload.o2ProofPackKey;
 
-    const nextReg = Array.isArray(rawRegistry)
-      ? [...rawRegistry, entry]
-      : [entry];
-    setRawRegistry(nextReg);
-    setProjects(registryToProjects(nextReg));
+    try {
+      const reg = (await invoke("o2_add_project", {
+        project: entry,
+      })) as RegistryLoad;
+      setRawRegistry(reg.projects);
+      setProjects(registryToProjects(reg.projects));
+      appendLog(`[projects] added ${payload.key} to ${reg.path}`);
+    } catch (e) {
+      appendLog("\n[projects] add failed:\n" + fmtErr(e));
+    }
+  }
 
-    const json = JSON.stringify(entry, null, 2);
-    appendLog(
-      "\n[projects] NEW REGISTRY ENTRY (paste into O2 projects.json):\n" + json,
+  // Only the fields the modal edits change; hooks, start spec and any
+  // fields RadControl doesn't model are sent back as they were.
+  async function updateProject(row: ProjectRow, payload: AddProjectPayload) {
+    const validation = validateAdd({
+      org: payload.org,
+      key: row.key,
+      port: payload.port,
+      url: payload.url,
+      repo: payload.repoPath,
+    });
+    if (!validation.ok) {
+      appendLog(`[projects] update rejected: ${validation.errors.join(" ")}`);
+      return;
+    }
+
+    const entry: Record<string, unknown> = {
+      ...row,
+      label: payload.label,
+      repoPath: payload.repoPath,
+      org: payload.org,
+      kind: payload.kind,
+    };
+    if (!row.repoHint || row.repoHint === row.repoPath)
+      entry.repoHint = payload.repoPath;
+    if (typeof payload.port === "number") entry.port = payload.port;
+    else delete entry.port;
+    if (payload.url) entry.url = payload.url;
+    else delete entry.url;
+
+    try {
+      const reg = (await invoke("o2_update_project", {
+        key: row.key,
+        project: entry,
+      })) as RegistryLoad;
+      setRawRegistry(reg.projects);
+      setProjects(registryToProjects(reg.projects));
+      appendLog(`[projects] updated ${row.key} in ${reg.path}`);
+    } catch (e) {
+      appendLog("\n[projects] update failed:\n" + fmtErr(e));
+    }
+  }
+
+  async function removeProject(row: ProjectRow) {
+    const ok = window.confirm(
+      `Remove ${row.label} (${row.key}) from the registry?\n\n` +
+        "Nothing on disk is deleted, and projects.json is backed up first.",
     );
-    void copyText(json);
+    if (!ok) return;
+
+    try {
+      const reg = (await invoke("o2_remove_project", {
+        key: row.key,
+      })) as RegistryLoad;
+      setRawRegistry(reg.projects);
+      setProjects(registryToProjects(reg.projects));
+      appendLog(`[projects] removed ${row.key} from ${reg.path}`);
+    } catch (e) {
+      appendLog("\n[projects] remove failed:\n" + fmtErr(e));
+    }
   }
 
-  const logText = (busy ? "Running…" : log || "No logs yet.").toString();
+  const logText = (log || (busy ? "Running…" : "No logs yet.")).toString();
 
   return (
     <div className="appShell">
@@ -545,10 +1127,22 @@ export default function App() {
             className="btn"
             onClick={() => void restartRadcontrol()}
             disabled={busy}
-            title="Restart RadControl (dev_strict) and refresh project status. Does not start/open projects."
+            title={
+              verbs["radcontrol.dev_strict"]?.description ??
+              "Restart RadControl (dev_strict) and refresh project status. Does not start/open projects."
+            }
           >
             Restart + Refresh Status
           </button>
+
+          <button
+            className="btn btnDanger"
+            onClick={() => void panicStop()}
+            disabled={busy}
+            title="Stop running jobs, supervised servers and project ports, and write an incident report"
+          >
+            Panic Stop
+          </button>
         </div>
       </header>
 
@@ -561,9 +1155,12 @@ export default function App() {
               <div className="projectsHeaderRight">
                 <button
                   className="btn btnPrimary"
-                  onClick={() => setShowAddProject(true)}
+                  onClick={() => {
+                    setEditingProject(null);
+                    setShowAddProject(true);
+                  }}
                   disabled={busy}
-                  title="Add a project (UI-only; copies JSON to clipboard)"
+                  title="Add a project to the O2 registry"
                 >
                   New Project
                 </button>
@@ -585,12 +1182,22 @@ export default function App() {
               busy={busy}
               portsBusy={portsBusy}
               onWorkOn={workOnProject}
-              onSnapshot={(p) =>
-                void runO2(`Snapshot ${p.label}`, p.o2SnapshotKey)
-              }
-              onCommit={(p) => void runO2(`Commit ${p.label}`, p.o2CommitKey)}
+              onSnapshot={(p) => void snapshotProject(p)}
+              onCommit={(p) => void commitProject(p)}
               onKill={freePort}
-              onMap={(p) => void runO2(`${p.label} Map`, p.o2MapKey)}
+              onMap={(p) => void mapProject(p)}
+              onSessionStart={(p) => void sessionStart(p)}
+              gitForRow={(p) => gitStatus[p.key]}
+              resourcesForRow={(p) => resources[p.key]?.current ?? undefined}
+              supervisedForRow={(p) => supervised[p.key]}
+              onStop={(p) => void superviseProject(p, "stop")}
+              onRestart={(p) => void superviseProject(p, "restart")}
+              onLogs={(p) => void showLogs(p)}
+              onEdit={(p) => {
+                setEditingProject(p);
+                setShowAddProject(true);
+              }}
+              onRemove={(p) => void removeProject(p)}
               onProofPack={(p) =>
                 void runO2(`${p.label} Proof Pack`, p.o2ProofPackKey)
               }
@@ -599,9 +1206,17 @@ export default function App() {
 
             <AddProjectModal
               open={showAddProject}
-              onClose={() => setShowAddProject(false)}
-              onCreate={createProject}
+              onClose={() => {
+                setShowAddProject(false);
+                setEditingProject(null);
+              }}
+              onCreate={(payload) =>
+                editingProject
+                  ? updateProject(editingProject, payload)
+                  : createProject(payload)
+              }
               defaultSuggestedPort={suggestedPort}
+              initial={editingProject}
             />
           </div>
         ) : (
@@ -629,6 +1244,15 @@ export default function App() {
         <div className="logsBoxRow">
           <div className="logsBox">{logText}</div>
           <div className="logsActionsStack">
+            {busy && currentJobId ? (
+              <button
+                className="btn btnDanger"
+                onClick={() => void cancelCurrentJob()}
+                title="SIGTERM the running O2 job (SIGKILL after a grace period)"
+              >
+                Cancel
+              </button>
+            ) : null}
             <button
               className="btn btnGhost"
               onClick={() => void copyText(logText)}
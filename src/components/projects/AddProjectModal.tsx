@@ -1,5 +1,5 @@
 import { useEffect, useMemo, useState } from "react";
-import type { AddProjectPayload } from "./types";
+import type { AddProjectPayload, ProjectRow } from "./types";
 import { slugify, inferRepoPath, asPort, validateAdd } from "./helpers";
 
 type Org = "radcon" | "radwolfe" | "labs" | "other";
@@ -10,12 +10,15 @@ export function AddProjectModal({
   onClose,
   onCreate,
   defaultSuggestedPort,
+  initial,
 }: {
   open: boolean;
   onClose: () => void;
   onCreate: (payload: AddProjectPayload) => Promise<void> | void;
   defaultSuggestedPort?: number;
   usedPorts?: Set<number>;
+  // Edit an existing row instead of adding one; its key can't change.
+  initial?: ProjectRow | null;
 }) {
   const [key, setKey] = useState("");
   const [label, setLabel] = useState("");
@@ -32,16 +35,26 @@ export function AddProjectModal({
   // Reset on open so it doesn't get "stuck" between uses.
   useEffect(() => {
     if (!open) return;
-    setKey("");
-    setLabel("");
-    setOrg("radcon");
-    setRepoPath("");
-    setKind("nextjs");
-    setPort(defaultSuggestedPort ? String(defaultSuggestedPort) : "");
-    setUrl("");
+    if (initial) {
+      setKey(initial.key);
+      setLabel(initial.label ?? "");
+      setOrg((initial.org as Org) ?? "other");
+      setRepoPath(initial.repoPath ?? initial.repoHint ?? "");
+      setKind((initial.kind as Kind) ?? "other");
+      setPort(typeof initial.port === "number" ? String(initial.port) : "");
+      setUrl(initial.url ?? "");
+    } else {
+      setKey("");
+      setLabel("");
+      setOrg("radcon");
+      setRepoPath("");
+      setKind("nextjs");
+      setPort(defaultSuggestedPort ? String(defaultSuggestedPort) : "");
+      setUrl("");
+    }
     setErr(null);
     setSaving(false);
-  }, [open, defaultSuggestedPort]);
+  }, [open, defaultSuggestedPort, initial]);
 
   const keySlug = useMemo(() => slugify(key), [key]);
 
@@ -99,7 +112,9 @@ export function AddProjectModal({
       setErr(
         typeof e?.message === "string" && e.message.trim()
           ? e.message
-          : "Failed to create project.",
+          : initial
+            ? "Failed to save project."
+            : "Failed to create project.",
       );
     } finally {
       setSaving(false);
@@ -117,7 +132,9 @@ export function AddProjectModal({
     >
       <div className="modalCard">
         <div className="modalHeader">
-          <div className="modalTitle">Add Project</div>
+          <div className="modalTitle">
+            {initial ? `Edit ${initial.label || initial.key}` : "Add Project"}
+          </div>
           <button className="btn btnGhost" onClick={onClose} disabled={saving}>
             Close
           </button>
@@ -129,7 +146,7 @@ export function AddProjectModal({
             value={key}
             onChange={(e) => setKey(e.target.value)}
             placeholder="tbis"
-            disabled={saving}
+            disabled={saving || Boolean(initial)}
           />
           <div style={{ fontSize: 12, opacity: 0.85, marginTop: 6 }}>
             Saved key: <code>{keySlug || "—"}</code>
@@ -238,7 +255,13 @@ export function AddProjectModal({
             disabled={saving || Boolean(validationError)}
             type="button"
           >
-            {saving ? "Creating…" : "Create"}
+            {initial
+              ? saving
+                ? "Saving…"
+                : "Save"
+              : saving
+                ? "Creating…"
+                : "Create"}
           </button>
         </div>
       </div>
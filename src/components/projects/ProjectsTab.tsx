@@ -1,4 +1,10 @@
-import type { ProjectRow, PortStatus } from "./types";
+import type {
+  ProjectRow,
+  PortStatus,
+  ProjectGitStatus,
+  SupervisedProcess,
+  ResourceSample,
+} from "./types";
 
 type StatusLike = {
   pill: string;
@@ -15,11 +21,56 @@ type Props = {
   onCommit: (p: ProjectRow) => Promise<void> | void;
   onKill: (port: number) => Promise<void> | void;
   onMap: (p: ProjectRow) => Promise<void> | void;
+  onSessionStart: (p: ProjectRow) => Promise<void> | void;
   onProofPack: (p: ProjectRow) => Promise<void> | void;
   statusForRow: (p: ProjectRow) => StatusLike | unknown;
+  gitForRow?: (p: ProjectRow) => ProjectGitStatus | undefined;
+  resourcesForRow?: (p: ProjectRow) => ResourceSample | undefined;
+  supervisedForRow?: (p: ProjectRow) => SupervisedProcess | undefined;
+  onStop?: (p: ProjectRow) => Promise<void> | void;
+  onRestart?: (p: ProjectRow) => Promise<void> | void;
+  onLogs?: (p: ProjectRow) => Promise<void> | void;
+  onEdit?: (p: ProjectRow) => void;
+  onRemove?: (p: ProjectRow) => Promise<void> | void;
   killDisabledReason?: string;
 };
 
+function gitSummary(g: ProjectGitStatus | undefined): string | null {
+  const s = g?.git;
+  if (!s) return null;
+
+  const parts = [s.branch ?? `detached ${s.head?.slice(0, 7) ?? ""}`.trim()];
+  if (s.ahead || s.behind) parts.push(`↑${s.ahead} ↓${s.behind}`);
+  if (s.rebaseInProgress) parts.push("REBASING");
+  if (s.mergeInProgress) parts.push("MERGING");
+  if (s.conflicted) parts.push(`${s.conflicted} conflicted`);
+  if (s.staged) parts.push(`${s.staged} staged`);
+  if (s.unstaged) parts.push(`${s.unstaged} modified`);
+  if (s.untracked) parts.push(`${s.untracked} untracked`);
+  if (!g?.dirty) parts.push("clean");
+  return parts.join(" · ");
+}
+
+function fmtDuration(ms: number): string {
+  const m = Math.floor(ms / 60000);
+  if (m < 60) return `${m}m`;
+  const h = Math.floor(m / 60);
+  return h < 48 ? `${h}h ${m % 60}m` : `${Math.floor(h / 24)}d`;
+}
+
+function resourceSummary(r: ResourceSample): string {
+  const mb = r.rssBytes / (1024 * 1024);
+  const parts = [
+    r.cpuPercent != null ? `cpu ${r.cpuPercent.toFixed(1)}%` : "cpu …",
+    mb >= 1024 ? `${(mb / 1024).toFixed(1)} GB` : `${Math.round(mb)} MB`,
+    `${r.pids} proc`,
+    `${r.threads} thr`,
+    `${r.fds} fds`,
+  ];
+  if (r.uptimeMs != null) parts.push(`up ${fmtDuration(r.uptimeMs)}`);
+  return parts.join(" · ");
+}
+
 export function ProjectsTab({
   projects,
   ports,
@@ -30,8 +81,17 @@ export function ProjectsTab({
   onCommit,
   onKill,
   onMap,
+  onSessionStart,
   onProofPack,
   statusForRow,
+  gitForRow,
+  resourcesForRow,
+  supervisedForRow,
+  onStop,
+  onRestart,
+  onLogs,
+  onEdit,
+  onRemove,
   killDisabledReason,
 }: Props) {
   const safeStatusForRow = (p: ProjectRow): StatusLike => {
@@ -66,6 +126,12 @@ export function ProjectsTab({
           const s = typeof port === "number" ? ports[port] : undefined;
 
           const isListening = Boolean(s?.listening);
+          const gitRow = gitForRow?.(p);
+          const git = gitRow?.git ? gitRow : undefined;
+          const res = resourcesForRow?.(p);
+          const sup = supervisedForRow?.(p);
+          const supervisedUp =
+            sup?.state === "running" || sup?.state === "stopping";
           const killDisabled =
             busy || portsBusy || typeof port !== "number" || !isListening;
 
@@ -76,6 +142,20 @@ export function ProjectsTab({
                 {p.repoHint ? (
                   <div className="projectHint">{p.repoHint}</div>
                 ) : null}
+                {git ? (
+                  <div
+                    className={
+                      git.dirty ? "projectGit projectGitDirty" : "projectGit"
+                    }
+                    title={git.git?.lastCommit?.subject ?? undefined}
+                  >
+                    {git.dirty ? "● " : ""}
+                    {gitSummary(git)}
+                  </div>
+                ) : null}
+                {res ? (
+                  <div className="projectGit">{resourceSummary(res)}</div>
+                ) : null}
               </div>
 
               <div className="projectRight">
@@ -87,6 +167,26 @@ export function ProjectsTab({
                   Work on
                 </button>
 
+                {p.start && supervisedUp ? (
+                  <>
+                    <button
+                      className="btn"
+                      onClick={() => onRestart?.(p)}
+                      disabled={busy || sup?.state === "stopping"}
+                      title={`Supervised pid ${sup?.pid}${sup?.adopted ? " (adopted)" : ""}`}
+                    >
+                      Restart
+                    </button>
+                    <button
+                      className="btn btnDanger"
+                      onClick={() => onStop?.(p)}
+                      disabled={busy || sup?.state === "stopping"}
+                    >
+                      Stop
+                    </button>
+                  </>
+                ) : null}
+
                 <button
                   className="btn"
                   onClick={() => onSnapshot(p)}
@@ -113,7 +213,7 @@ export function ProjectsTab({
                     typeof port !== "number"
                       ? "No port"
                       : isListening
-                        ? "Kill listener via O2 kill_port.<port>"
+                        ? "Stop the process tree listening on this port"
                         : "Not running"
                   }
                 >
@@ -128,6 +228,25 @@ export function ProjectsTab({
                   Map
                 </button>
 
+                <button
+                  className="btn btnGhost"
+                  onClick={() => onSessionStart(p)}
+                  disabled={busy}
+                  title="Git status, repo index and snapshot"
+                >
+                  Session
+                </button>
+
+                {onLogs ? (
+                  <button
+                    className="btn btnGhost"
+                    onClick={() => onLogs(p)}
+                    title="Last 200 lines of captured output"
+                  >
+                    Logs
+                  </button>
+                ) : null}
+
                 {p.o2ProofPackKey ? (
                   <button
                     className="btn btnGhost"
@@ -137,6 +256,28 @@ export function ProjectsTab({
                     Proof Pack
                   </button>
                 ) : null}
+
+                {onEdit ? (
+                  <button
+                    className="btn btnGhost"
+                    onClick={() => onEdit(p)}
+                    disabled={busy}
+                    title="Edit this registry row"
+                  >
+                    Edit
+                  </button>
+                ) : null}
+
+                {onRemove ? (
+                  <button
+                    className="btn btnGhost"
+                    onClick={() => onRemove(p)}
+                    disabled={busy}
+                    title="Remove this row from the registry"
+                  >
+                    Remove
+                  </button>
+                ) : null}
               </div>
 
               <div className="projectMid">
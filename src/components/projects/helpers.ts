@@ -144,8 +144,15 @@ export function validateAdd(args: {
     errors.push("URL must start with http:// or https://");
 
   const repo = typeof args.repo === "string" ? args.repo.trim() : "";
-  if (repo.length > 0 && !repo.startsWith("$HOME/") && !repo.startsWith("/")) {
-    errors.push("Repo path should be absolute (/...) or start with $HOME/...");
+  if (
+    repo.length > 0 &&
+    !repo.startsWith("$HOME/") &&
+    !repo.startsWith("~/") &&
+    !repo.startsWith("/")
+  ) {
+    errors.push(
+      "Repo path should be absolute (/...) or start with $HOME/... or ~/...",
+    );
   }
 
   return { ok: errors.length === 0, errors };
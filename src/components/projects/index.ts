@@ -1,4 +1,10 @@
-export type { ProjectRow, PortStatus, AddProjectPayload } from "./types";
+export type {
+  ProjectRow,
+  PortStatus,
+  AddProjectPayload,
+  RegistryLoad,
+  RegistryRowError,
+} from "./types";
 
 export {
   fmtErr,
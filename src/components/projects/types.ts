@@ -19,6 +19,38 @@ export type ProjectRow = {
   // Map / ProofPack
   o2MapKey?: string;
   o2ProofPackKey?: string;
+
+  // Meta
+  org?: string;
+  kind?: string;
+  repoPath?: string;
+
+  // Health check: substring expected in the body served at `url`
+  healthExpect?: string;
+
+  // Supervisor: launch the dev server natively instead of via o2StartKey
+  start?: StartSpec;
+};
+
+/** argv (not a shell line), cwd relative to the checkout, extra env. */
+export type StartSpec = {
+  command: string[];
+  cwd?: string;
+  env?: Record<string, string>;
+};
+
+/** A registry row the backend skipped, with the reason. */
+export type RegistryRowError = {
+  index: number;
+  key?: string | null;
+  message: string;
+};
+
+/** What o2_list_projects returns. */
+export type RegistryLoad = {
+  path: string;
+  projects: ProjectRow[];
+  errors: RegistryRowError[];
 };
 
 export type ProjectOrg = "radcon" | "radwolfe" | "labs" | "other";
@@ -69,3 +101,285 @@ export type PortStatus = {
   cmd?: string | null;
   err?: string | null;
 };
+
+export type ProcInfo = {
+  pid: number;
+  ppid: number;
+  cmdline?: string | null;
+  cwd?: string | null;
+  startedAtMs?: number | null;
+};
+
+/** o2_port_owners: what a kill would stop. */
+export type PortOwners = {
+  port: number;
+  processes: ProcInfo[];
+  holders: number[];
+  protectedReason?: string | null;
+};
+
+/** o2_kill_port result. */
+export type KillReport = {
+  port: number;
+  targeted: number[];
+  exited: number[];
+  killed: number[];
+  survivors: number[];
+};
+
+/** o2_health_check: HTTP GET against a project's url. */
+export type HealthStatus = {
+  key: string;
+  url: string;
+  ok: boolean;
+  // False for urls the backend can't probe (https); never unhealthy then.
+  checked: boolean;
+  status?: number | null;
+  latencyMs?: number | null;
+  bodyMatched?: boolean | null;
+  err?: string | null;
+};
+
+/** o2_project_status: port + health folded per registry row. */
+export type ProjectStatus = {
+  key: string;
+  state: "no_port" | "stopped" | "running" | "unhealthy";
+  port?: PortStatus | null;
+  health?: HealthStatus | null;
+};
+
+/** o2_snapshot_project: native docs/_repo_snapshot.txt writer. */
+export type SnapshotSummary = {
+  name: string;
+  repo: string;
+  path: string;
+  branch?: string | null;
+  commit?: string | null;
+  dirtyFiles: number;
+  recentCommits: string[];
+  bytes: number;
+  generatedAtMs: number;
+};
+
+/** o2_index_project: native docs/_o2_repo_index.{txt,json} writer. */
+export type IndexSummary = {
+  name: string;
+  repo: string;
+  textPath: string;
+  jsonPath: string;
+  profileSource: string;
+  filesListed: number;
+  hotspotMatches: number;
+  bytes: number;
+  generatedAtMs: number;
+};
+
+/** o2_git_status: branch/tracking/worktree state of a project's checkout. */
+export type RepoGitStatus = {
+  branch?: string | null;
+  head?: string | null;
+  upstream?: string | null;
+  ahead: number;
+  behind: number;
+  staged: number;
+  unstaged: number;
+  untracked: number;
+  conflicted: number;
+  lastCommit?: { hash: string; subject: string; timeMs: number } | null;
+  rebaseInProgress: boolean;
+  mergeInProgress: boolean;
+};
+
+export type ProjectGitStatus = {
+  key: string;
+  repo?: string | null;
+  git?: RepoGitStatus | null;
+  dirty: boolean;
+  err?: string | null;
+};
+
+export type FileChange = {
+  path: string;
+  insertions?: number | null;
+  deletions?: number | null;
+};
+
+export type DiffSummary = {
+  files: FileChange[];
+  insertions: number;
+  deletions: number;
+};
+
+/** o2_commit_preview: what the commit verb would pick up. */
+export type CommitPreview = {
+  key: string;
+  repo: string;
+  verb: string;
+  branch?: string | null;
+  head?: string | null;
+  staged: DiffSummary;
+  unstaged: DiffSummary;
+  untracked: string[];
+  diff: string;
+  diffTruncated: boolean;
+  token: string;
+};
+
+/** o2_commit result; `run` keeps run_o2's snake_case fields. */
+export type CommitResult = {
+  key: string;
+  verb: string;
+  jobId: string;
+  run: {
+    ok: boolean;
+    code: number;
+    stdout: string;
+    stderr: string;
+    cancelled: boolean;
+    timed_out: boolean;
+    elapsed_ms: number;
+  };
+  previousHead?: string | null;
+  newHead?: string | null;
+  committed: boolean;
+};
+
+/** o2_list_verbs: allowlisted verbs with their catalog metadata. */
+export type VerbParam = {
+  name: string;
+  type: "port" | "projectKey" | "text";
+  required: boolean;
+  maxLen?: number;
+};
+
+export type VerbInfo = {
+  verb: string;
+  source: "catalog" | "registry" | "builtin";
+  project?: string | null;
+  params: VerbParam[];
+  description?: string;
+  category?: string;
+  destructive?: boolean;
+  confirmPhrase?: string;
+  expectedDurationMs?: number;
+  refreshPorts?: boolean;
+};
+
+/** A catalog entry or registry hook o2_list_verbs skipped. */
+export type VerbError = {
+  source: "catalog" | "registry" | "builtin";
+  project?: string | null;
+  verb?: string | null;
+  message: string;
+};
+
+export type VerbLoad = {
+  verbs: VerbInfo[];
+  errors: VerbError[];
+};
+
+/** o2_prepare_verb: one-shot confirmation token for a destructive verb. */
+export type PreparedVerb = {
+  verb: string;
+  args: Record<string, string>;
+  token: string;
+  expiresAtMs: number;
+  destructive: boolean;
+  description?: string | null;
+  confirmPhrase?: string | null;
+};
+
+/** workflow-step / workflow-finished: progress of an o2_run_workflow run. */
+export type WorkflowStep = {
+  index: number;
+  name: string;
+  status: "running" | "succeeded" | "failed" | "timed_out" | "skipped";
+  startedAtMs?: number | null;
+  endedAtMs?: number | null;
+  jobId?: string | null;
+  detail?: unknown;
+  error?: string | null;
+};
+
+export type WorkflowStepEvent = {
+  runId: string;
+  workflow: string;
+  total: number;
+  step: WorkflowStep;
+};
+
+export type WorkflowReport = {
+  runId: string;
+  workflow: string;
+  project: string;
+  startedAtMs: number;
+  endedAtMs: number;
+  ok: boolean;
+  steps: WorkflowStep[];
+};
+
+/** o2_supervisor_*: a dev server owned by the backend supervisor. */
+export type SupervisedProcess = {
+  key: string;
+  pid: number;
+  command: string[];
+  cwd: string;
+  port?: number | null;
+  procStartedAtMs?: number | null;
+  startedAtMs: number;
+  state: "running" | "stopping" | "exited";
+  exitCode?: number | null;
+  endedAtMs?: number | null;
+  adopted: boolean;
+  portOwned?: boolean | null;
+};
+
+/** o2_logs_tail / o2_logs_search / log-line: one captured output line. */
+export type LogLine = {
+  seq: number;
+  atMs: number;
+  stream: "stdout" | "stderr" | "supervisor";
+  line: string;
+  jobId?: string;
+};
+
+/** o2_resource_usage / resource-usage: totals over a project's process tree. */
+export type ResourceSample = {
+  atMs: number;
+  pids: number;
+  cpuPercent?: number | null;
+  rssBytes: number;
+  threads: number;
+  fds: number;
+  uptimeMs?: number | null;
+};
+
+export type ProjectResources = {
+  key: string;
+  rootPid?: number | null;
+  current?: ResourceSample | null;
+  history: ResourceSample[];
+};
+
+/** o2_panic_stop: what was (or would be) stopped, and the incident record. */
+export type PanicTarget = {
+  order: number;
+  kind: "job" | "supervised" | "port";
+  key?: string | null;
+  port?: number | null;
+  jobId?: string | null;
+  processes: ProcInfo[];
+  skipReason?: string | null;
+  /** Couldn't be inspected; not stopped, reported as a failed outcome. */
+  error?: string | null;
+};
+
+export type PanicReport = {
+  dryRun: boolean;
+  startedAtMs: number;
+  endedAtMs: number;
+  targets: PanicTarget[];
+  outcomes: { order: number; ok: boolean; detail: string }[];
+  reportPath?: string | null;
+  registryError?: string | null;
+};